@@ -0,0 +1,55 @@
+use aoc_2020::seating::ferry::{rule::SeatingRule, simulation::Simulation, FerrySeating};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+const SIZE: usize = 2000;
+const GENERATIONS: usize = 10;
+
+/// A square layout where about one cell in eight is floor, the same every run
+fn large_layout() -> FerrySeating {
+    let mut state: u32 = 0x2020_1211;
+    let mut layout = String::with_capacity(SIZE * (SIZE + 1));
+    for _row in 0..SIZE {
+        for _col in 0..SIZE {
+            // xorshift
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            // the top three bits are all zero one time in eight
+            layout.push(if state >> 29 == 0 { '.' } else { 'L' });
+        }
+        layout.push('\n');
+    }
+    layout.parse().unwrap()
+}
+
+fn ferry_benchmark(c: &mut Criterion) {
+    let seating = large_layout();
+
+    let mut group = c.benchmark_group("ferry 2000x2000");
+    group.sample_size(10);
+
+    for (name, rule) in &[
+        ("adjacent", SeatingRule::ADJACENT),
+        ("line of sight", SeatingRule::LINE_OF_SIGHT),
+    ] {
+        let simulation = Simulation::new(&seating, rule);
+        group.bench_function(format!("{} generations, {}", GENERATIONS, name), |b| {
+            // cloning the simulation is not part of the measurement
+            b.iter_batched(
+                || simulation.clone(),
+                |mut simulation| {
+                    for _ in 0..GENERATIONS {
+                        simulation.step();
+                    }
+                    simulation.count_occupied_seats()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, ferry_benchmark);
+criterion_main!(benches);
@@ -3,14 +3,30 @@ use std::{
 };
 
 pub fn get_rules_from_file(file: &str) -> BagRules {
-    let contents = std::fs::read_to_string(file).unwrap();
+    read_rules_from_file(file)
+        .unwrap_or_else(|(line, e)| panic!("invalid bag rule on line {}: {}", line, e))
+}
+
+/// Reads the bag rules in `file`, one rule per line
+///
+/// # Returns
+/// * the 1-indexed line number and the error of the first rule that can't be parsed
+pub fn read_rules_from_file(file: &str) -> Result<BagRules, (usize, ParseBagRuleError)> {
+    parse_rules(&std::fs::read_to_string(file).unwrap())
+}
+
+fn parse_rules(contents: &str) -> Result<BagRules, (usize, ParseBagRuleError)> {
     let mut rules = BagRules::new();
-    for line in contents.split('\n').filter(|l| !l.trim().is_empty()) {
-        let rule = line.parse().unwrap();
+    for (idx, line) in contents.split('\n').enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let rule = line.parse().map_err(|e| (idx + 1, e))?;
         rules.add_rule(rule);
     }
 
-    rules
+    Ok(rules)
 }
 
 pub struct BagRules {
@@ -83,6 +99,127 @@ impl BagRules {
 
         count
     }
+
+    /// Returns every chain of bags leading from `outer` to `target`
+    ///
+    /// Each path starts with `outer` and ends with `target`, so a bag directly inside `outer` gives a path of length 2
+    pub fn paths_between(&self, outer: &str, target: &str) -> Vec<Vec<String>> {
+        let mut paths = Vec::new();
+        let mut current = vec![outer.to_owned()];
+
+        self.collect_paths(target, &mut current, &mut paths);
+
+        paths
+    }
+
+    fn collect_paths(&self, target: &str, current: &mut Vec<String>, paths: &mut Vec<Vec<String>>) {
+        let last = current.last().unwrap().clone(); // safe to unwrap because the path always contains the outer bag
+
+        if let Some(b) = self.rules().get(&last) {
+            for inner in b.inner_bags() {
+                if current.contains(&inner.0) {
+                    // a cyclic rule set would otherwise never terminate
+                    continue;
+                }
+
+                current.push(inner.0.clone());
+                if inner.0 == target {
+                    paths.push(current.clone());
+                } else {
+                    self.collect_paths(target, current, paths);
+                }
+                current.pop();
+            }
+        }
+    }
+
+    /// Returns how many levels of bags are nested inside `start`
+    ///
+    /// A bag that contains no other bags has a depth of 0
+    /// # Returns
+    /// * `None` if `start` ends up containing itself or another bag that does, since the nesting never ends
+    pub fn max_depth(&self, start: &str) -> Option<u32> {
+        let mut current = vec![start];
+
+        self.depth_below(&mut current)
+    }
+
+    fn depth_below<'a>(&'a self, current: &mut Vec<&'a str>) -> Option<u32> {
+        let last = *current.last().unwrap(); // safe to unwrap because the path always contains the start bag
+
+        let mut depth = 0;
+        if let Some(b) = self.rules().get(last) {
+            for inner in b.inner_bags() {
+                if current.contains(&inner.0.as_str()) {
+                    return None;
+                }
+
+                current.push(&inner.0);
+                depth = depth.max(1 + self.depth_below(current)?);
+                current.pop();
+            }
+        }
+
+        Some(depth)
+    }
+
+    /// Returns the total number of each bag color required inside `outer`, at any level of nesting
+    /// # Returns
+    /// * `None` if `outer` ends up containing itself or another bag that does, since no number of bags is enough
+    pub fn contents(&self, outer: &str) -> Option<HashMap<String, u32>> {
+        let mut contents = HashMap::new();
+        let mut current = vec![outer];
+
+        self.add_contents(&mut current, 1, &mut contents)?;
+
+        Some(contents)
+    }
+
+    fn add_contents<'a>(
+        &'a self,
+        current: &mut Vec<&'a str>,
+        multiplier: u32,
+        contents: &mut HashMap<String, u32>,
+    ) -> Option<()> {
+        let last = *current.last().unwrap(); // safe to unwrap because the path always contains the outer bag
+
+        if let Some(b) = self.rules().get(last) {
+            for inner in b.inner_bags() {
+                if current.contains(&inner.0.as_str()) {
+                    return None;
+                }
+
+                let count = multiplier * inner.1;
+                *contents.entry(inner.0.clone()).or_insert(0) += count;
+
+                current.push(&inner.0);
+                self.add_contents(current, count, contents)?;
+                current.pop();
+            }
+        }
+
+        Some(())
+    }
+
+    /// Returns the colors of all bags that can't contain any other bags
+    ///
+    /// This includes colors that are only mentioned as inner bags and have no rule of their own
+    pub fn leaf_bags(&self) -> HashSet<&str> {
+        let rules = self.rules();
+
+        let with_rules = rules
+            .values()
+            .filter(|b| b.inner_bags().is_empty())
+            .map(|b| b.outer_bag_color());
+
+        let without_rules = rules
+            .values()
+            .flat_map(|b| b.inner_bags())
+            .map(|inner| inner.0.as_str())
+            .filter(|color| !rules.contains_key(*color));
+
+        with_rules.chain(without_rules).collect()
+    }
 }
 
 impl Default for BagRules {
@@ -118,42 +255,252 @@ impl FromStr for BagRule {
     type Err = ParseBagRuleError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let p: Vec<&str> = s.split("bags contain").collect();
+        // grammar:
+        // rule     = color "bags" "contain" contents "."
+        // contents = "no" "other" "bags" | inner ("," inner)*
+        // inner    = count color ("bag" | "bags")
+
+        let mut parser = RuleParser::new(s);
+
+        let outer_color = parser.outer_color()?;
+        parser.expect("contain", "\"contain\"")?;
+
+        let inner_bags = if parser.peek_text() == Some("no") {
+            parser.expect("no", "\"no\"")?;
+            parser.expect("other", "\"other\"")?;
+            parser.expect("bags", "\"bags\"")?;
+            Vec::new()
+        } else {
+            let mut inner_bags = vec![parser.inner_bag()?];
+            while parser.peek_text() == Some(",") {
+                parser.next("\",\"")?;
+                inner_bags.push(parser.inner_bag()?);
+            }
+            inner_bags
+        };
+
+        parser.expect(".", "\".\"")?;
+        parser.end()?;
+
+        Ok(BagRule::new(outer_color, inner_bags))
+    }
+}
+
+/// A word or punctuation mark in a bag rule, with its byte offset in the input
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Token<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+
+    for (idx, c) in s.char_indices() {
+        if c.is_whitespace() || c == ',' || c == '.' {
+            if let Some(start) = word_start.take() {
+                tokens.push(Token {
+                    text: &s[start..idx],
+                    position: start,
+                });
+            }
+            if !c.is_whitespace() {
+                tokens.push(Token {
+                    text: &s[idx..idx + 1],
+                    position: idx,
+                });
+            }
+        } else if word_start.is_none() {
+            word_start = Some(idx);
+        }
+    }
 
-        if p.len() != 2 {
-            return Err(ParseBagRuleError::SplitLengthNot2);
+    if let Some(start) = word_start {
+        tokens.push(Token {
+            text: &s[start..],
+            position: start,
+        });
+    }
+
+    tokens
+}
+
+struct RuleParser<'a> {
+    tokens: Vec<Token<'a>>,
+    curr: usize,
+}
+
+impl<'a> RuleParser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            tokens: tokenize(s),
+            curr: 0,
         }
+    }
 
-        let outer_color = p[0].trim().to_owned();
-        let inner_bags: Vec<(String, u32)> = p[1]
-            .trim()
-            .split(',')
-            .map(|p| p.trim().to_owned())
-            .filter_map(|b| {
-                let words: Vec<_> = b.split(' ').collect();
-                if words.len() < 3 {
-                    None
-                } else {
-                    let count: u32 = words[0].trim().parse().ok()?;
-                    let inner_bag_color = words[1..words.len() - 1].join(" ").trim().to_owned();
-                    Some((inner_bag_color, count))
-                }
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.curr).copied()
+    }
+
+    fn peek_text(&self) -> Option<&'a str> {
+        self.peek().map(|t| t.text)
+    }
+
+    fn next(&mut self, expected: &'static str) -> Result<Token<'a>, ParseBagRuleError> {
+        let token = self
+            .peek()
+            .ok_or(ParseBagRuleError::UnexpectedEnd { expected })?;
+        self.curr += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, text: &str, expected: &'static str) -> Result<(), ParseBagRuleError> {
+        let token = self.next(expected)?;
+        if token.text == text {
+            Ok(())
+        } else {
+            Err(ParseBagRuleError::UnexpectedToken {
+                position: token.position,
+                expected,
+                found: token.text.to_owned(),
             })
-            .collect();
+        }
+    }
 
-        Ok(BagRule::new(outer_color, inner_bags))
+    fn end(&self) -> Result<(), ParseBagRuleError> {
+        match self.peek() {
+            Some(token) => Err(ParseBagRuleError::TrailingInput {
+                position: token.position,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads the words of a color up to and including the "bag" or "bags" that follows it
+    fn color(&mut self) -> Result<(String, Token<'a>), ParseBagRuleError> {
+        const EXPECTED_NOUN: &str = "\"bag\" or \"bags\"";
+
+        let mut words: Vec<Token> = Vec::new();
+        loop {
+            let token = self.next(EXPECTED_NOUN)?;
+            match token.text {
+                "bag" | "bags" if words.is_empty() => {
+                    return Err(ParseBagRuleError::MissingColor {
+                        position: token.position,
+                    })
+                }
+                "bag" | "bags" => {
+                    let color = words.iter().map(|w| w.text).collect::<Vec<_>>().join(" ");
+                    return Ok((color, token));
+                }
+                text if text == "," || text == "." || text.chars().all(|c| c.is_ascii_digit()) => {
+                    // the last word is most likely a misspelled "bag" or "bags",
+                    // since colors never contain numbers and a number starts the next inner bag
+                    let culprit = words.last().copied().unwrap_or(token);
+                    return Err(ParseBagRuleError::UnexpectedToken {
+                        position: culprit.position,
+                        expected: EXPECTED_NOUN,
+                        found: culprit.text.to_owned(),
+                    });
+                }
+                _ => words.push(token),
+            }
+        }
+    }
+
+    fn outer_color(&mut self) -> Result<String, ParseBagRuleError> {
+        let (color, noun) = self.color()?;
+        if noun.text != "bags" {
+            return Err(ParseBagRuleError::UnexpectedToken {
+                position: noun.position,
+                expected: "\"bags\"",
+                found: noun.text.to_owned(),
+            });
+        }
+
+        Ok(color)
+    }
+
+    fn inner_bag(&mut self) -> Result<(String, u32), ParseBagRuleError> {
+        let count_token = self.next("a bag count")?;
+        let count: u32 = match count_token.text.parse() {
+            Ok(c) if c > 0 => c,
+            _ => {
+                return Err(ParseBagRuleError::InvalidCount {
+                    position: count_token.position,
+                    found: count_token.text.to_owned(),
+                })
+            }
+        };
+
+        let (color, noun) = self.color()?;
+        let expected_noun = if count == 1 { "bag" } else { "bags" };
+        if noun.text != expected_noun {
+            return Err(ParseBagRuleError::WrongBagNoun {
+                position: noun.position,
+                count,
+                found: noun.text.to_owned(),
+            });
+        }
+
+        Ok((color, count))
     }
 }
 
-#[derive(Debug)]
-enum ParseBagRuleError {
-    SplitLengthNot2,
+/// An error from parsing a bag rule, with positions given as byte offsets into the rule
+#[derive(Debug, PartialEq)]
+pub enum ParseBagRuleError {
+    /// The rule ended while `expected` was still missing
+    UnexpectedEnd { expected: &'static str },
+    /// `found` was encountered where `expected` should have been
+    UnexpectedToken {
+        position: usize,
+        expected: &'static str,
+        found: String,
+    },
+    /// The number of inner bags was not a positive integer
+    InvalidCount { position: usize, found: String },
+    /// "bag" was used for several bags, or "bags" for a single bag
+    WrongBagNoun {
+        position: usize,
+        count: u32,
+        found: String,
+    },
+    /// "bag" or "bags" was not preceded by a color
+    MissingColor { position: usize },
+    /// There was more input after the final period
+    TrailingInput { position: usize },
 }
 
 impl Display for ParseBagRuleError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let output = match self {
-            ParseBagRuleError::SplitLengthNot2 => "split length was not 2",
+            ParseBagRuleError::UnexpectedEnd { expected } => {
+                format!("expected {} but the rule ended", expected)
+            }
+            ParseBagRuleError::UnexpectedToken {
+                position,
+                expected,
+                found,
+            } => format!("expected {} at {}, found '{}'", expected, position, found),
+            ParseBagRuleError::InvalidCount { position, found } => {
+                format!("invalid bag count '{}' at {}", found, position)
+            }
+            ParseBagRuleError::WrongBagNoun {
+                position,
+                count,
+                found,
+            } => format!(
+                "'{}' at {} does not agree with count {}",
+                found, position, count
+            ),
+            ParseBagRuleError::MissingColor { position } => {
+                format!("missing bag color before {}", position)
+            }
+            ParseBagRuleError::TrailingInput { position } => {
+                format!("unexpected input after the rule at {}", position)
+            }
         };
 
         write!(f, "{}", output)
@@ -179,4 +526,158 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn parse_empty_rule_test() {
+        assert_eq!(
+            BagRule::from_str("faded blue bags contain no other bags.").unwrap(),
+            BagRule::new("faded blue".to_string(), vec![])
+        );
+    }
+
+    #[test]
+    fn parse_invalid_rule_test() {
+        assert_eq!(
+            BagRule::from_str("dark olive bags contain 2 shiny gold bgs.").unwrap_err(),
+            ParseBagRuleError::UnexpectedToken {
+                position: 37,
+                expected: "\"bag\" or \"bags\"",
+                found: "bgs".to_string()
+            }
+        );
+
+        assert_eq!(
+            BagRule::from_str("dark olive bags contain 2 shiny gold bgs 3 red bags.").unwrap_err(),
+            ParseBagRuleError::UnexpectedToken {
+                position: 37,
+                expected: "\"bag\" or \"bags\"",
+                found: "bgs".to_string()
+            }
+        );
+
+        assert_eq!(
+            BagRule::from_str("dark olive bags contain 1 shiny gold bags.").unwrap_err(),
+            ParseBagRuleError::WrongBagNoun {
+                position: 37,
+                count: 1,
+                found: "bags".to_string()
+            }
+        );
+
+        assert_eq!(
+            BagRule::from_str("dark olive bags contain 0 shiny gold bags.").unwrap_err(),
+            ParseBagRuleError::InvalidCount {
+                position: 24,
+                found: "0".to_string()
+            }
+        );
+
+        assert_eq!(
+            BagRule::from_str("dark olive bags contain no other bags").unwrap_err(),
+            ParseBagRuleError::UnexpectedEnd { expected: "\".\"" }
+        );
+
+        assert_eq!(
+            BagRule::from_str("dark olive bags contain no other bags. extra").unwrap_err(),
+            ParseBagRuleError::TrailingInput { position: 39 }
+        );
+    }
+
+    #[test]
+    fn parse_rules_test() {
+        let rules = parse_rules(
+            "faded blue bags contain no other bags.\n\nshiny gold bags contain 3 faded blue bags.\n",
+        )
+        .unwrap();
+        assert_eq!(rules.count_bags_inside("shiny gold"), 3);
+
+        assert_eq!(
+            parse_rules(
+                "faded blue bags contain no other bags.\nshiny gold bags contain 3 faded blue.\n"
+            )
+            .err(),
+            Some((
+                2,
+                ParseBagRuleError::UnexpectedToken {
+                    position: 32,
+                    expected: "\"bag\" or \"bags\"",
+                    found: "blue".to_string()
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn query_rules_test() {
+        let rules = example_rules();
+
+        assert_eq!(
+            rules.paths_between("light red", "shiny gold"),
+            vec![
+                vec!["light red", "bright white", "shiny gold"],
+                vec!["light red", "muted yellow", "shiny gold"],
+            ]
+        );
+        assert_eq!(rules.max_depth("shiny gold"), Some(2));
+        assert_eq!(rules.max_depth("faded blue"), Some(0));
+
+        let contents = rules.contents("shiny gold").unwrap();
+        assert_eq!(contents["dark olive"], 1);
+        assert_eq!(contents["vibrant plum"], 2);
+        assert_eq!(contents["faded blue"], 3 + 10);
+        assert_eq!(contents["dotted black"], 4 + 12);
+        assert_eq!(
+            contents.values().sum::<u32>(),
+            rules.count_bags_inside("shiny gold")
+        );
+
+        assert_eq!(
+            rules.leaf_bags(),
+            vec!["faded blue", "dotted black"].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn cyclic_rules_test() {
+        let mut rules = BagRules::new();
+        for line in &[
+            "light red bags contain 1 a b bag.",
+            "a b bags contain 1 c d bag.",
+            "c d bags contain 2 a b bags.",
+            "faded blue bags contain no other bags.",
+        ] {
+            rules.add_rule(line.parse().unwrap());
+        }
+
+        assert_eq!(rules.max_depth("a b"), None);
+        assert_eq!(rules.max_depth("light red"), None);
+        assert_eq!(rules.max_depth("faded blue"), Some(0));
+
+        assert_eq!(rules.contents("a b"), None);
+        assert_eq!(rules.contents("light red"), None);
+        assert_eq!(rules.contents("faded blue"), Some(HashMap::new()));
+
+        assert_eq!(
+            rules.paths_between("light red", "c d"),
+            vec![vec!["light red", "a b", "c d"]]
+        );
+    }
+
+    fn example_rules() -> BagRules {
+        let input = "light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+        let mut rules = BagRules::new();
+        for line in input.lines() {
+            rules.add_rule(line.parse().unwrap());
+        }
+        rules
+    }
 }
@@ -218,7 +218,10 @@ mod tests {
         use super::*;
 
         fn solve_day4_part1_from_file(file: &str) -> usize {
-            passport::read_valid_passports_from_file(file).len()
+            passport::read_raw_passports_from_file(file)
+                .iter()
+                .filter(|p| p.validate(&passport::ValidationMode::PresenceOnly).is_ok())
+                .count()
         }
 
         fn solve_day4_part2_from_file(file: &str) -> usize {
@@ -228,7 +231,7 @@ mod tests {
         #[test]
         fn day4_part1() {
             assert_eq!(solve_day4_part1_from_file("inputs/day4_example.txt"), 2);
-            // assert_eq!(solve_day4_part1_from_file("inputs/day4.txt"), 233); // TODO
+            assert_eq!(solve_day4_part1_from_file("inputs/day4.txt"), 233);
         }
 
         #[test]
@@ -241,7 +244,7 @@ mod tests {
     mod day5 {
         use std::str::FromStr;
 
-        use crate::seating::airplane::Seating;
+        use crate::seating::airplane::{Geometry, SeatMap, Seating};
 
         use super::*;
 
@@ -255,22 +258,14 @@ mod tests {
         }
 
         fn solve_day5_part2_from_file(file: &str) -> u32 {
-            let mut ids: Vec<_> = read_lines_from_file(&file)
+            let seatings = read_lines_from_file(&file)
                 .into_iter()
-                .map(|p| Seating::from_str(&p).unwrap())
-                .map(|s| s.id())
-                .collect();
-
-            ids.sort();
-
-            for w in ids.windows(2) {
-                let (before, after) = (w[0], w[1]);
-                if after - before == 2 {
-                    return after - 1;
-                }
-            }
+                .map(|p| Seating::from_str(&p).unwrap());
+            let map = SeatMap::new(Geometry::STANDARD, seatings);
 
-            unreachable!()
+            let candidates = map.empty_seats_between_occupied();
+            assert_eq!(candidates.len(), 1, "expected exactly one free seat");
+            candidates[0].id()
         }
 
         #[test]
@@ -1,30 +1,153 @@
+pub mod optimizer;
 pub mod ship;
+pub mod simulator;
+pub mod trace;
 pub mod waypoint;
 
-use std::str::FromStr;
+use std::{convert::TryFrom, fmt::Display, str::FromStr};
 
-#[derive(Default, Copy, Clone, Debug)]
-struct Position {
+/// A position where x grows to the east and y grows to the north
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Position {
     x: isize,
     y: isize,
 }
 
 impl Position {
-    fn new(x: isize, y: isize) -> Self {
+    pub fn new(x: isize, y: isize) -> Self {
         Self { x, y }
     }
+
+    pub fn x(&self) -> isize {
+        self.x
+    }
+
+    pub fn y(&self) -> isize {
+        self.y
+    }
+
+    /// Moves `distance` units toward `direction`, along both axes for intercardinal directions
+    pub fn move_toward(
+        &mut self,
+        direction: Direction,
+        distance: u32,
+    ) -> Result<(), NavigationError> {
+        self.translate(direction.offset(), distance)
+    }
+
+    /// Moves by `(dx, dy)` the given number of times, leaving the position as it was if it would overflow
+    pub fn translate(
+        &mut self,
+        (dx, dy): (isize, isize),
+        times: u32,
+    ) -> Result<(), NavigationError> {
+        let times = isize::try_from(times).map_err(|_| NavigationError::Overflow)?;
+        let x = dx.checked_mul(times).and_then(|dx| self.x.checked_add(dx));
+        let y = dy.checked_mul(times).and_then(|dy| self.y.checked_add(dy));
+
+        match (x, y) {
+            (Some(x), Some(y)) => {
+                self.x = x;
+                self.y = y;
+                Ok(())
+            }
+            _ => Err(NavigationError::Overflow),
+        }
+    }
+
+    /// The distance from the origin moving only along the axes
+    ///
+    /// # Returns
+    /// * `None` if the distance doesn't fit in an `isize`
+    pub fn manhattan_distance(&self) -> Option<isize> {
+        self.x.checked_abs()?.checked_add(self.y.checked_abs()?)
+    }
 }
 
-#[derive(Debug)]
-enum Direction {
+/// The directions of a compass rose, in clockwise order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
     North,
+    NorthEast,
     East,
+    SouthEast,
     South,
+    SouthWest,
     West,
+    NorthWest,
+}
+
+impl Direction {
+    const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// # Returns
+    /// * the direction at `bearing` degrees clockwise from north, or `None` if it's not a multiple of 45
+    pub fn from_bearing(bearing: i64) -> Option<Direction> {
+        if bearing % 45 != 0 {
+            return None;
+        }
+        Some(Direction::ALL[(bearing.rem_euclid(360) / 45) as usize])
+    }
+
+    /// The degrees clockwise from north
+    pub fn bearing(&self) -> i64 {
+        45 * Direction::ALL.iter().position(|d| d == self).unwrap() as i64 // safe to unwrap because ALL has every direction
+    }
+
+    pub fn is_cardinal(&self) -> bool {
+        self.bearing() % 90 == 0
+    }
+
+    /// One step toward the direction, as (x, y)
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, 1),
+            Direction::NorthEast => (1, 1),
+            Direction::East => (1, 0),
+            Direction::SouthEast => (1, -1),
+            Direction::South => (0, -1),
+            Direction::SouthWest => (-1, -1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (-1, 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NavigationError {
+    /// A turn that is not a multiple of the angles the movement model can turn by
+    UnsupportedTurn { degrees: i64, multiple_of: i64 },
+    /// A move that would take the ship or its waypoint out of the coordinates that can be represented
+    Overflow,
+}
+
+impl Display for NavigationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NavigationError::UnsupportedTurn {
+                degrees,
+                multiple_of,
+            } => write!(
+                f,
+                "can't turn {} degrees, only multiples of {}",
+                degrees, multiple_of
+            ),
+            NavigationError::Overflow => write!(f, "the move goes out of bounds"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum Instruction {
+pub enum Instruction {
     MoveNorth(u32),
     MoveEast(u32),
     MoveSouth(u32),
@@ -59,7 +182,7 @@ impl FromStr for Instruction {
     }
 }
 
-fn read_instr_from_file(file: &str) -> Vec<Instruction> {
+pub fn read_instr_from_file(file: &str) -> Vec<Instruction> {
     let content = std::fs::read_to_string(file).unwrap();
 
     content
@@ -72,6 +195,9 @@ fn read_instr_from_file(file: &str) -> Vec<Instruction> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ship::DirectHeading;
+    use simulator::Simulator;
+    use waypoint::Waypoint;
 
     #[test]
     fn parse_instruction_test() {
@@ -80,4 +206,33 @@ mod tests {
             Instruction::MoveNorth(3)
         );
     }
+
+    #[test]
+    fn example_route_test() {
+        let instructions: Vec<Instruction> = vec!["F10", "N3", "F7", "R90", "F11"]
+            .into_iter()
+            .map(|i| i.parse().unwrap())
+            .collect();
+
+        let mut simulator = Simulator::new(DirectHeading::new());
+        simulator.run(instructions.clone()).unwrap();
+        assert_eq!(simulator.ship(), Position::new(17, -8));
+        assert_eq!(simulator.model().facing_direction(), Direction::South);
+
+        let mut simulator = Simulator::new(Waypoint::new());
+        simulator.run(instructions).unwrap();
+        assert_eq!(simulator.ship(), Position::new(214, -72));
+        assert_eq!(simulator.model().waypoint(), Position::new(4, -10));
+        assert_eq!(simulator.ship().manhattan_distance(), Some(286));
+    }
+
+    #[test]
+    fn manhattan_distance_overflow_test() {
+        assert_eq!(
+            Position::new(isize::MAX, 0).manhattan_distance(),
+            Some(isize::MAX)
+        );
+        assert_eq!(Position::new(isize::MAX, 1).manhattan_distance(), None);
+        assert_eq!(Position::new(0, isize::MIN).manhattan_distance(), None);
+    }
 }
@@ -0,0 +1,184 @@
+use super::Instruction;
+
+/// Folds consecutive instructions that add up into as few instructions as possible
+///
+/// * a run of `N`, `E`, `S` and `W` becomes at most one north or south and one east or west move
+/// * a run of `L` and `R` becomes at most one turn, of less than 360 degrees
+///
+/// The folded route ends in the same place with every movement model in this crate,
+/// but it only records the folded steps in a trace, and it may succeed where the original
+/// fails on an intermediate turn or overflow, like `L45` followed by `L45` for a ship that can only turn by 90 degrees.
+pub fn optimize(instructions: &[Instruction]) -> Vec<Instruction> {
+    let mut optimized = Vec::with_capacity(instructions.len());
+    let mut idx = 0;
+
+    while idx < instructions.len() {
+        let run = instructions[idx..]
+            .iter()
+            .take_while(|i| kind(i) == kind(&instructions[idx]))
+            .count();
+        let run_instructions = &instructions[idx..idx + run];
+
+        match kind(&instructions[idx]) {
+            Kind::Shift => fold_shifts(run_instructions, &mut optimized),
+            Kind::Turn => fold_turns(run_instructions, &mut optimized),
+            Kind::Forward => optimized.extend_from_slice(run_instructions),
+        }
+
+        idx += run;
+    }
+
+    optimized
+}
+
+#[derive(PartialEq)]
+enum Kind {
+    Shift,
+    Turn,
+    Forward,
+}
+
+fn kind(instruction: &Instruction) -> Kind {
+    match instruction {
+        Instruction::MoveNorth(_)
+        | Instruction::MoveEast(_)
+        | Instruction::MoveSouth(_)
+        | Instruction::MoveWest(_) => Kind::Shift,
+        Instruction::TurnLeft(_) | Instruction::TurnRight(_) => Kind::Turn,
+        Instruction::MoveForward(_) => Kind::Forward,
+    }
+}
+
+fn fold_shifts(shifts: &[Instruction], optimized: &mut Vec<Instruction>) {
+    let (mut north, mut east) = (0i64, 0i64);
+    for shift in shifts {
+        match *shift {
+            Instruction::MoveNorth(v) => north += i64::from(v),
+            Instruction::MoveEast(v) => east += i64::from(v),
+            Instruction::MoveSouth(v) => north -= i64::from(v),
+            Instruction::MoveWest(v) => east -= i64::from(v),
+            _ => unreachable!("only shifts are folded here"),
+        }
+    }
+
+    push_split(
+        north,
+        Instruction::MoveNorth,
+        Instruction::MoveSouth,
+        optimized,
+    );
+    push_split(
+        east,
+        Instruction::MoveEast,
+        Instruction::MoveWest,
+        optimized,
+    );
+}
+
+fn fold_turns(turns: &[Instruction], optimized: &mut Vec<Instruction>) {
+    let left: i64 = turns
+        .iter()
+        .map(|turn| match *turn {
+            Instruction::TurnLeft(v) => i64::from(v),
+            Instruction::TurnRight(v) => -i64::from(v),
+            _ => unreachable!("only turns are folded here"),
+        })
+        .sum();
+
+    // turn the short way around, so that right turns stay right turns in the common case
+    match left.rem_euclid(360) {
+        0 => {}
+        degrees if degrees <= 180 => optimized.push(Instruction::TurnLeft(degrees as u32)),
+        degrees => optimized.push(Instruction::TurnRight((360 - degrees) as u32)),
+    }
+}
+
+/// Pushes `value` as instructions of at most `u32::MAX`, with `positive` or `negative` depending on its sign
+fn push_split(
+    value: i64,
+    positive: fn(u32) -> Instruction,
+    negative: fn(u32) -> Instruction,
+    optimized: &mut Vec<Instruction>,
+) {
+    let instruction = if value >= 0 { positive } else { negative };
+    let mut remaining = value.unsigned_abs();
+    while remaining > 0 {
+        let part = remaining.min(u64::from(u32::MAX));
+        optimized.push(instruction(part as u32));
+        remaining -= part;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::navigation::{
+        ship::DirectHeading,
+        simulator::{MovementModel, Simulator},
+        waypoint::Waypoint,
+        Position,
+    };
+
+    fn parse(instructions: &[&str]) -> Vec<Instruction> {
+        instructions.iter().map(|i| i.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn optimize_test() {
+        let route = parse(&[
+            "N3", "E2", "S10", "W2", "F10", "R90", "L180", "R90", "F7", "F1", "L90", "R450", "R90",
+        ]);
+
+        assert_eq!(optimize(&route), parse(&["S7", "F10", "F7", "F1", "R90"]));
+    }
+
+    #[test]
+    fn long_route_test() {
+        fn replay<M: MovementModel>(model: M, route: &[Instruction]) -> Position {
+            let mut simulator = Simulator::new(model);
+            simulator.run(route.iter().copied()).unwrap();
+            simulator.ship()
+        }
+
+        // a deterministic pseudo-random route, mostly of shifts and turns
+        let mut state: u32 = 12;
+        let route: Vec<Instruction> = (0..1_000_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let value = state >> 16;
+                match value % 16 {
+                    0..=4 => Instruction::MoveNorth(value % 50),
+                    5..=8 => Instruction::MoveWest(value % 50),
+                    9 => Instruction::MoveSouth(value % 50),
+                    10 => Instruction::MoveEast(value % 50),
+                    11 | 12 => Instruction::TurnLeft(90 * (value % 4)),
+                    13 | 14 => Instruction::TurnRight(90 * (value % 4)),
+                    _ => Instruction::MoveForward(value % 10),
+                }
+            })
+            .collect();
+
+        let optimized = optimize(&route);
+        assert!(optimized.len() < route.len() * 3 / 4);
+
+        assert_eq!(
+            replay(DirectHeading::new(), &optimized),
+            replay(DirectHeading::new(), &route)
+        );
+        assert_eq!(
+            replay(Waypoint::new(), &optimized),
+            replay(Waypoint::new(), &route)
+        );
+    }
+
+    #[test]
+    fn overflow_test() {
+        let mut simulator = Simulator::new(Waypoint::at(Position::new(isize::MAX / 2, 0)));
+        simulator.step(Instruction::MoveForward(2)).unwrap();
+        assert_eq!(
+            simulator.step(Instruction::MoveForward(1)),
+            Err(crate::navigation::NavigationError::Overflow)
+        );
+        assert_eq!(simulator.ship(), Position::new(isize::MAX - 1, 0));
+    }
+}
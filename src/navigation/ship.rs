@@ -1,103 +1,219 @@
-use super::*;
+use super::{
+    simulator::{MovementModel, Simulator},
+    *,
+};
+
+/// The directions a ship can face
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compass {
+    /// North, east, south and west, turning by multiples of 90 degrees
+    Cardinal,
+    /// Also the directions in between, turning by multiples of 45 degrees
+    Intercardinal,
+}
+
+impl Compass {
+    fn step(&self) -> i64 {
+        match self {
+            Compass::Cardinal => 90,
+            Compass::Intercardinal => 45,
+        }
+    }
+}
 
+/// The ship moves itself: `N`, `E`, `S` and `W` move the ship, and `F` moves it toward where it is facing
 #[derive(Debug)]
-pub struct Ship {
+pub struct DirectHeading {
     facing_direction: Direction,
-    position: Position,
+    compass: Compass,
 }
 
-impl Ship {
+impl DirectHeading {
+    /// A ship facing east, that can only turn by multiples of 90 degrees
     pub fn new() -> Self {
+        Self::with_compass(Compass::Cardinal)
+    }
+
+    /// A ship facing east
+    pub fn with_compass(compass: Compass) -> Self {
         Self {
             facing_direction: Direction::East,
-            position: Position::default(),
+            compass,
         }
     }
 
-    pub fn set_pos(&mut self, (x, y): (isize, isize)) {
-        self.position.x = x;
-        self.position.y = y;
+    pub fn facing_direction(&self) -> Direction {
+        self.facing_direction
     }
+}
 
-    pub fn x_pos(&self) -> isize {
-        self.position.x
+impl Default for DirectHeading {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    pub fn y_pos(&self) -> isize {
-        self.position.y
+impl MovementModel for DirectHeading {
+    type State = Direction;
+
+    fn state(&self) -> Direction {
+        self.facing_direction
     }
 
-    fn handle_instruction(&mut self, instr: Instruction) {
-        match instr {
-            Instruction::MoveNorth(v) => self.position.y += v as isize,
-            Instruction::MoveEast(v) => self.position.x += v as isize,
-            Instruction::MoveSouth(v) => self.position.y -= v as isize,
-            Instruction::MoveWest(v) => self.position.x -= v as isize,
-            Instruction::TurnLeft(v) => self.turn_left(v),
-            Instruction::TurnRight(v) => self.turn_right(v),
-            Instruction::MoveForward(v) => self.move_forward(v),
-        }
+    fn shift(
+        &mut self,
+        ship: &mut Position,
+        direction: Direction,
+        distance: u32,
+    ) -> Result<(), NavigationError> {
+        ship.move_toward(direction, distance)
     }
 
-    fn move_forward(&mut self, v: u32) {
-        match self.facing_direction {
-            Direction::North => self.position.y += v as isize,
-            Direction::East => self.position.x += v as isize,
-            Direction::South => self.position.y -= v as isize,
-            Direction::West => self.position.x -= v as isize,
+    fn turn(&mut self, _ship: &mut Position, degrees: i64) -> Result<(), NavigationError> {
+        let multiple_of = self.compass.step();
+        if degrees % multiple_of != 0 {
+            return Err(NavigationError::UnsupportedTurn {
+                degrees,
+                multiple_of,
+            });
         }
+
+        // bearings grow clockwise, so turning left makes them smaller
+        let bearing = self.facing_direction.bearing() - degrees;
+        self.facing_direction = Direction::from_bearing(bearing).unwrap(); // safe to unwrap because bearing is a multiple of 45
+        Ok(())
     }
 
-    fn turn_left(&mut self, mut degrees: u32) {
-        assert!(degrees % 90 == 0);
+    fn forward(&mut self, ship: &mut Position, distance: u32) -> Result<(), NavigationError> {
+        ship.move_toward(self.facing_direction, distance)
+    }
+}
 
-        degrees %= 360;
-        let steps = degrees / 90;
+/// Like `DirectHeading`, but the ship can turn by any number of degrees
+///
+/// The ship still ends up on whole units after each move: the fraction that is left over is carried
+/// to the next move, so the error doesn't add up over many moves.
+#[derive(Debug)]
+pub struct ContinuousHeading {
+    /// Degrees clockwise from north, between 0 and 359
+    bearing: i64,
+    remainder: (f64, f64),
+}
 
-        for _ in 0..steps {
-            match self.facing_direction {
-                Direction::North => self.facing_direction = Direction::West,
-                Direction::East => self.facing_direction = Direction::North,
-                Direction::South => self.facing_direction = Direction::East,
-                Direction::West => self.facing_direction = Direction::South,
-            }
+impl ContinuousHeading {
+    /// A ship facing east
+    pub fn new() -> Self {
+        Self {
+            bearing: Direction::East.bearing(),
+            remainder: (0.0, 0.0),
         }
     }
 
-    fn turn_right(&mut self, mut degrees: u32) {
-        assert!(degrees % 90 == 0);
-
-        degrees %= 360;
-        let steps = degrees / 90;
-
-        for _ in 0..steps {
-            match self.facing_direction {
-                Direction::North => self.facing_direction = Direction::East,
-                Direction::East => self.facing_direction = Direction::South,
-                Direction::South => self.facing_direction = Direction::West,
-                Direction::West => self.facing_direction = Direction::North,
-            }
-        }
+    /// The degrees clockwise from north the ship is facing
+    pub fn bearing(&self) -> i64 {
+        self.bearing
     }
 }
 
-impl Default for Ship {
+impl Default for ContinuousHeading {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl MovementModel for ContinuousHeading {
+    /// The bearing
+    type State = i64;
+
+    fn state(&self) -> i64 {
+        self.bearing
+    }
+
+    fn shift(
+        &mut self,
+        ship: &mut Position,
+        direction: Direction,
+        distance: u32,
+    ) -> Result<(), NavigationError> {
+        ship.move_toward(direction, distance)
+    }
+
+    fn turn(&mut self, _ship: &mut Position, degrees: i64) -> Result<(), NavigationError> {
+        self.bearing = (self.bearing - degrees).rem_euclid(360);
+        Ok(())
+    }
+
+    fn forward(&mut self, ship: &mut Position, distance: u32) -> Result<(), NavigationError> {
+        // exact along the axes, where sin and cos would be off by a tiny fraction
+        if let Some(direction) = Direction::from_bearing(self.bearing).filter(|d| d.is_cardinal()) {
+            return ship.move_toward(direction, distance);
+        }
+
+        let radians = (self.bearing as f64).to_radians();
+        let dx = self.remainder.0 + f64::from(distance) * radians.sin();
+        let dy = self.remainder.1 + f64::from(distance) * radians.cos();
+        let (whole_x, whole_y) = (dx.round(), dy.round());
+
+        ship.translate((whole_x as isize, whole_y as isize), 1)?;
+        self.remainder = (dx - whole_x, dy - whole_y);
+        Ok(())
+    }
+}
+
 pub fn solve_day12_part1_from_file(file: &str) -> isize {
     let instrs = read_instr_from_file(file);
 
-    let mut ship = Ship::new();
+    let mut simulator = Simulator::new(DirectHeading::new());
+    simulator.run(instrs).unwrap();
+
+    simulator.ship().manhattan_distance().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    for instr in instrs {
-        ship.handle_instruction(instr);
+    fn run<M: MovementModel>(model: M, instructions: &[&str]) -> Simulator<M> {
+        let mut simulator = Simulator::new(model);
+        for instruction in instructions {
+            simulator.step(instruction.parse().unwrap()).unwrap();
+        }
+        simulator
     }
 
-    let x_dist = ship.position.x.abs();
-    let y_dist = ship.position.y.abs();
+    #[test]
+    fn intercardinal_test() {
+        let simulator = run(
+            DirectHeading::with_compass(Compass::Intercardinal),
+            &["L45", "F3", "R135", "F2", "L450"],
+        );
+        assert_eq!(simulator.ship(), Position::new(3, 1));
+        assert_eq!(simulator.model().facing_direction(), Direction::East);
+
+        let mut simulator = Simulator::new(DirectHeading::new());
+        assert_eq!(
+            simulator.step(Instruction::TurnRight(45)),
+            Err(NavigationError::UnsupportedTurn {
+                degrees: -45,
+                multiple_of: 90
+            })
+        );
+        assert_eq!(simulator.model().facing_direction(), Direction::East);
+    }
 
-    x_dist + y_dist
+    #[test]
+    fn continuous_heading_test() {
+        let simulator = run(
+            ContinuousHeading::new(),
+            &["L30", "F10", "F10", "R120", "F4"],
+        );
+        // 20 units at 60 degrees from north is (17.32, 10), and the last move is straight south
+        assert_eq!(simulator.ship(), Position::new(17, 6));
+        assert_eq!(simulator.model().bearing(), 180);
+
+        // the fractions carried over add up instead of being lost
+        let steps = vec!["F1"; 100];
+        let simulator = run(ContinuousHeading::new(), &[&["L60"][..], &steps].concat());
+        assert_eq!(simulator.ship(), Position::new(50, 87));
+    }
 }
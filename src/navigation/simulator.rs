@@ -0,0 +1,165 @@
+use super::{
+    trace::{Trace, TraceEntry},
+    Direction, Instruction, NavigationError, Position,
+};
+
+/// How the instructions move a ship
+///
+/// The simulator decides which method an instruction calls, so a model only says what each kind of instruction does.
+pub trait MovementModel {
+    /// What the model steers by, like the direction the ship faces or its waypoint
+    type State: Clone;
+
+    fn state(&self) -> Self::State;
+
+    /// Handles `N`, `E`, `S` and `W`
+    fn shift(
+        &mut self,
+        ship: &mut Position,
+        direction: Direction,
+        distance: u32,
+    ) -> Result<(), NavigationError>;
+
+    /// Handles `L` and `R`, where positive degrees turn left (counterclockwise) and negative degrees turn right
+    ///
+    /// Like the other methods, a model returns an error if it can't follow the instruction,
+    /// and must not change anything in that case.
+    fn turn(&mut self, ship: &mut Position, degrees: i64) -> Result<(), NavigationError>;
+
+    /// Handles `F`
+    fn forward(&mut self, ship: &mut Position, distance: u32) -> Result<(), NavigationError>;
+}
+
+/// Replays instructions on a ship that starts at the origin, moving it with a `MovementModel`
+#[derive(Debug, Clone)]
+pub struct Simulator<M: MovementModel> {
+    model: M,
+    ship: Position,
+    trace: Option<Trace<M::State>>,
+}
+
+impl<M: MovementModel> Simulator<M> {
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            ship: Position::default(),
+            trace: None,
+        }
+    }
+
+    /// Records every instruction and where it left the ship from now on
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Trace::new(self.ship));
+        self
+    }
+
+    /// # Returns
+    /// * the route so far, if it is being recorded
+    pub fn trace(&self) -> Option<&Trace<M::State>> {
+        self.trace.as_ref()
+    }
+
+    pub fn ship(&self) -> Position {
+        self.ship
+    }
+
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    pub fn step(&mut self, instruction: Instruction) -> Result<(), NavigationError> {
+        let ship = &mut self.ship;
+        match instruction {
+            Instruction::MoveNorth(v) => self.model.shift(ship, Direction::North, v)?,
+            Instruction::MoveEast(v) => self.model.shift(ship, Direction::East, v)?,
+            Instruction::MoveSouth(v) => self.model.shift(ship, Direction::South, v)?,
+            Instruction::MoveWest(v) => self.model.shift(ship, Direction::West, v)?,
+            Instruction::TurnLeft(v) => self.model.turn(ship, i64::from(v))?,
+            Instruction::TurnRight(v) => self.model.turn(ship, -i64::from(v))?,
+            Instruction::MoveForward(v) => self.model.forward(ship, v)?,
+        }
+
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                instruction,
+                position: self.ship,
+                state: self.model.state(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Replays `instructions` in order, stopping at the first one that fails
+    pub fn run<I: IntoIterator<Item = Instruction>>(
+        &mut self,
+        instructions: I,
+    ) -> Result<(), NavigationError> {
+        for instruction in instructions {
+            self.step(instruction)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ship that drifts east with the current whenever it moves forward
+    struct Drifting {
+        heading: Direction,
+        drift: u32,
+    }
+
+    impl MovementModel for Drifting {
+        type State = Direction;
+
+        fn state(&self) -> Direction {
+            self.heading
+        }
+
+        fn shift(
+            &mut self,
+            ship: &mut Position,
+            direction: Direction,
+            distance: u32,
+        ) -> Result<(), NavigationError> {
+            ship.move_toward(direction, distance)
+        }
+
+        fn turn(&mut self, _ship: &mut Position, degrees: i64) -> Result<(), NavigationError> {
+            self.heading = if degrees > 0 {
+                Direction::North
+            } else {
+                Direction::South
+            };
+            Ok(())
+        }
+
+        fn forward(&mut self, ship: &mut Position, distance: u32) -> Result<(), NavigationError> {
+            let mut moved = *ship;
+            moved.move_toward(self.heading, distance)?;
+            moved.move_toward(Direction::East, self.drift)?;
+            *ship = moved;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn custom_model_test() {
+        let mut simulator = Simulator::new(Drifting {
+            heading: Direction::West,
+            drift: 1,
+        });
+
+        let instructions = vec!["F10", "N3", "L90", "F7", "R5", "F11"];
+        simulator
+            .run(instructions.into_iter().map(|i| i.parse().unwrap()))
+            .unwrap();
+
+        assert_eq!(simulator.ship(), Position::new(-7, -1));
+        assert_eq!(simulator.model().heading, Direction::South);
+    }
+}
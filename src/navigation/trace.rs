@@ -0,0 +1,235 @@
+use std::{collections::HashMap, fmt::Write};
+
+use super::{Instruction, Position};
+
+/// An instruction, with the position of the ship and the state of the movement model right after it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry<S> {
+    pub instruction: Instruction,
+    pub position: Position,
+    pub state: S,
+}
+
+/// The route of a ship, as recorded by a `Simulator`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace<S> {
+    start: Position,
+    entries: Vec<TraceEntry<S>>,
+}
+
+impl<S> Trace<S> {
+    pub fn new(start: Position) -> Self {
+        Self {
+            start,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, entry: TraceEntry<S>) {
+        self.entries.push(entry);
+    }
+
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    pub fn entries(&self) -> &[TraceEntry<S>] {
+        &self.entries
+    }
+
+    pub fn end(&self) -> Position {
+        self.entries.last().map_or(self.start, |e| e.position)
+    }
+
+    /// The start, followed by the position after each instruction
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        std::iter::once(self.start).chain(self.entries.iter().map(|e| e.position))
+    }
+
+    /// The distance from the start to the end, moving only along the axes
+    ///
+    /// # Returns
+    /// * `None` if the distance doesn't fit in an `isize`
+    pub fn manhattan_distance(&self) -> Option<isize> {
+        let end = self.end();
+        let dx = end.x.checked_sub(self.start.x)?.checked_abs()?;
+        let dy = end.y.checked_sub(self.start.y)?.checked_abs()?;
+        dx.checked_add(dy)
+    }
+
+    /// The distance from the start to the end in a straight line
+    pub fn euclidean_distance(&self) -> f64 {
+        euclidean(self.start, self.end())
+    }
+
+    /// The distance sailed, going in a straight line from each position to the next
+    pub fn path_length(&self) -> f64 {
+        let positions: Vec<_> = self.positions().collect();
+        positions.windows(2).map(|w| euclidean(w[0], w[1])).sum()
+    }
+
+    /// Finds the first time the ship comes back to a position it has left
+    ///
+    /// Positions are compared after each instruction, so crossing an earlier path between them doesn't count.
+    pub fn first_revisit(&self) -> Option<Revisit> {
+        let mut visited = HashMap::new();
+        let mut previous = None;
+
+        for (step, position) in self.positions().enumerate() {
+            // turning in place doesn't leave the position
+            if previous == Some(position) {
+                continue;
+            }
+            previous = Some(position);
+
+            if let Some(&first_step) = visited.get(&position) {
+                return Some(Revisit {
+                    position,
+                    first_step,
+                    step,
+                });
+            }
+            visited.insert(position, step);
+        }
+
+        None
+    }
+
+    /// Draws the route as an SVG polyline, with north pointing up and a dot at the start
+    pub fn to_svg(&self) -> String {
+        // widened so that the sizes and the flipped y coordinates can't overflow, even at the edges of an `isize`
+        let positions: Vec<(i128, i128)> = self
+            .positions()
+            .map(|p| (p.x as i128, p.y as i128))
+            .collect();
+        // safe to unwrap because there is always a start position
+        let min_x = positions.iter().map(|p| p.0).min().unwrap();
+        let max_x = positions.iter().map(|p| p.0).max().unwrap();
+        let min_y = positions.iter().map(|p| p.1).min().unwrap();
+        let max_y = positions.iter().map(|p| p.1).max().unwrap();
+
+        let margin = 1 + (max_x - min_x).max(max_y - min_y) / 20;
+        let points = positions
+            .iter()
+            .map(|(x, y)| format!("{},{}", x, -y))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut svg = String::new();
+        // writing to a string can't fail
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+            min_x - margin,
+            -max_y - margin,
+            max_x - min_x + 2 * margin,
+            max_y - min_y + 2 * margin
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <polyline points="{}" fill="none" stroke="black" stroke-width="2" vector-effect="non-scaling-stroke"/>"#,
+            points
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <circle cx="{}" cy="{}" r="{}" fill="red"/>"#,
+            self.start.x,
+            -(self.start.y as i128),
+            margin as f64 / 2.0
+        )
+        .unwrap();
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn euclidean(from: Position, to: Position) -> f64 {
+    let dx = to.x as f64 - from.x as f64;
+    let dy = to.y as f64 - from.y as f64;
+    dx.hypot(dy)
+}
+
+/// A position the ship comes back to, where steps count the instructions so far and 0 is the start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Revisit {
+    pub position: Position,
+    pub first_step: usize,
+    pub step: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::navigation::{ship::DirectHeading, simulator::Simulator, Direction};
+
+    fn traced_route(instructions: &[&str]) -> Trace<Direction> {
+        let mut simulator = Simulator::new(DirectHeading::new()).with_trace();
+        simulator
+            .run(instructions.iter().map(|i| i.parse().unwrap()))
+            .unwrap();
+        simulator.trace().unwrap().clone()
+    }
+
+    #[test]
+    fn trace_metrics_test() {
+        let trace = traced_route(&["F10", "N3", "F7", "R90", "F11"]);
+
+        assert_eq!(trace.entries().len(), 5);
+        assert_eq!(
+            trace.entries()[3],
+            TraceEntry {
+                instruction: Instruction::TurnRight(90),
+                position: Position::new(17, 3),
+                state: Direction::South
+            }
+        );
+        assert_eq!(trace.end(), Position::new(17, -8));
+        assert_eq!(trace.manhattan_distance(), Some(25));
+        assert!((trace.euclidean_distance() - 18.788).abs() < 0.001);
+        assert!((trace.path_length() - 31.0).abs() < f64::EPSILON);
+        assert_eq!(trace.first_revisit(), None);
+    }
+
+    #[test]
+    fn revisit_and_svg_test() {
+        let trace = traced_route(&["F2", "L90", "F1", "L90", "F2", "S1", "E2"]);
+
+        assert_eq!(
+            trace.first_revisit(),
+            Some(Revisit {
+                position: Position::new(0, 0),
+                first_step: 0,
+                step: 6
+            })
+        );
+
+        let svg = trace.to_svg();
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-1 -2 4 3">"#));
+        assert!(svg.contains(r#"points="0,0 2,0 2,0 2,-1 2,-1 0,-1 0,0 2,0""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn extreme_positions_test() {
+        let mut trace = Trace::new(Position::new(isize::MIN, isize::MIN));
+        trace.push(TraceEntry {
+            instruction: Instruction::MoveForward(1),
+            position: Position::new(isize::MAX, isize::MAX),
+            state: (),
+        });
+
+        assert_eq!(trace.manhattan_distance(), None);
+        assert!(trace.euclidean_distance() > isize::MAX as f64);
+
+        let svg = trace.to_svg();
+        assert!(svg.contains(&format!(
+            "points=\"{},{} {},{}\"",
+            isize::MIN,
+            -(isize::MIN as i128),
+            isize::MAX,
+            -(isize::MAX as i128)
+        )));
+    }
+}
@@ -1,77 +1,99 @@
-use super::{ship::Ship, Instruction, Position};
+use super::{
+    simulator::{MovementModel, Simulator},
+    Direction, NavigationError, Position,
+};
 
+/// The matrices rotating a vector counterclockwise by 0, 90, 180 and 270 degrees
+const ROTATIONS: [[[isize; 2]; 2]; 4] = [
+    [[1, 0], [0, 1]],
+    [[0, -1], [1, 0]],
+    [[-1, 0], [0, -1]],
+    [[0, 1], [-1, 0]],
+];
+
+/// The ship follows a waypoint: `N`, `E`, `S` and `W` move the waypoint, `L` and `R` rotate it around the ship,
+/// and `F` moves the ship to the waypoint a number of times
+///
+/// The waypoint is relative to the ship, so it moves along with it.
 #[derive(Debug)]
-pub struct ShipWithWaypoint {
-    ship: Ship,
+pub struct Waypoint {
     waypoint: Position,
 }
 
-impl ShipWithWaypoint {
-    fn new() -> Self {
-        Self {
-            ship: Ship::new(),
-            waypoint: Position::new(10, 1),
-        }
+impl Waypoint {
+    /// A waypoint 10 units east and 1 unit north of the ship
+    pub fn new() -> Self {
+        Self::at(Position::new(10, 1))
     }
 
-    fn handle_instruction(&mut self, instr: Instruction) {
-        match instr {
-            Instruction::MoveNorth(v) => self.waypoint.y += v as isize,
-            Instruction::MoveEast(v) => self.waypoint.x += v as isize,
-            Instruction::MoveSouth(v) => self.waypoint.y -= v as isize,
-            Instruction::MoveWest(v) => self.waypoint.x -= v as isize,
-            Instruction::TurnLeft(v) => self.rotate_waypoint_left(v),
-            Instruction::TurnRight(v) => self.rotate_waypoint_right(v),
-            Instruction::MoveForward(v) => self.move_toward_waypoint(v),
-        }
+    pub fn at(waypoint: Position) -> Self {
+        Self { waypoint }
+    }
+
+    pub fn waypoint(&self) -> Position {
+        self.waypoint
     }
 
-    fn move_toward_waypoint(&mut self, steps: u32) {
-        for _ in 0..steps {
-            let new_x = self.ship.x_pos() + self.waypoint.x;
-            let new_y = self.ship.y_pos() + self.waypoint.y;
-            self.ship.set_pos((new_x, new_y));
+    /// Rotates the waypoint counterclockwise around the ship
+    fn rotate_waypoint(&mut self, degrees: i64) -> Result<(), NavigationError> {
+        if degrees % 90 != 0 {
+            return Err(NavigationError::UnsupportedTurn {
+                degrees,
+                multiple_of: 90,
+            });
+        }
+
+        let [[a, b], [c, d]] = ROTATIONS[(degrees.rem_euclid(360) / 90) as usize];
+        let wp = self.waypoint;
+        // a waypoint that fits can always be rotated, except for isize::MIN which has no negation
+        let x = a.checked_mul(wp.x).zip(b.checked_mul(wp.y));
+        let y = c.checked_mul(wp.x).zip(d.checked_mul(wp.y));
+        match (x, y) {
+            (Some((ax, by)), Some((cx, dy))) => {
+                self.waypoint = Position::new(ax + by, cx + dy);
+                Ok(())
+            }
+            _ => Err(NavigationError::Overflow),
         }
     }
+}
 
-    fn rotate_waypoint_left(&mut self, mut degrees: u32) {
-        assert!(degrees % 90 == 0);
+impl Default for Waypoint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        degrees %= 360;
-        let steps = degrees / 90;
+impl MovementModel for Waypoint {
+    type State = Position;
 
-        for _ in 0..steps {
-            let wp = self.waypoint;
-            self.waypoint.x = -wp.y;
-            self.waypoint.y = wp.x;
-        }
+    fn state(&self) -> Position {
+        self.waypoint
     }
 
-    fn rotate_waypoint_right(&mut self, mut degrees: u32) {
-        assert!(degrees % 90 == 0);
+    fn shift(
+        &mut self,
+        _ship: &mut Position,
+        direction: Direction,
+        distance: u32,
+    ) -> Result<(), NavigationError> {
+        self.waypoint.move_toward(direction, distance)
+    }
 
-        degrees %= 360;
-        let steps = degrees / 90;
+    fn turn(&mut self, _ship: &mut Position, degrees: i64) -> Result<(), NavigationError> {
+        self.rotate_waypoint(degrees)
+    }
 
-        for _ in 0..steps {
-            let wp = self.waypoint;
-            self.waypoint.x = wp.y;
-            self.waypoint.y = -wp.x;
-        }
+    fn forward(&mut self, ship: &mut Position, steps: u32) -> Result<(), NavigationError> {
+        ship.translate((self.waypoint.x, self.waypoint.y), steps)
     }
 }
 
 pub fn solve_day12_part2_from_file(file: &str) -> isize {
     let instrs = super::read_instr_from_file(file);
 
-    let mut ship_with_waypoint = ShipWithWaypoint::new();
-
-    for instr in instrs {
-        ship_with_waypoint.handle_instruction(instr);
-    }
-
-    let x_dist = ship_with_waypoint.ship.x_pos().abs();
-    let y_dist = ship_with_waypoint.ship.y_pos().abs();
+    let mut simulator = Simulator::new(Waypoint::new());
+    simulator.run(instrs).unwrap();
 
-    x_dist + y_dist
+    simulator.ship().manhattan_distance().unwrap()
 }
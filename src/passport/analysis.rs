@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use super::Passport;
+
+/// Something suspicious found when looking at a batch of passports together
+///
+/// Passports are referred to by their index in the batch
+#[derive(Debug, Clone, PartialEq)]
+pub enum Finding {
+    /// Several passports share the same passport id
+    DuplicatePassportId {
+        passport_id: String,
+        indices: Vec<usize>,
+    },
+    /// A passport was issued after the year it expires
+    IssuedAfterExpiration {
+        index: usize,
+        issue_year: u32,
+        expiration_year: u32,
+    },
+    /// A passport expired before the reference year
+    Expired {
+        index: usize,
+        expiration_year: u32,
+        reference_year: u32,
+    },
+    /// Several passports have the same fields, except for different country ids
+    DifferentCountryIdOnly { indices: Vec<usize> },
+}
+
+/// Checks a batch of passports for duplicates and inconsistencies between their fields
+///
+/// A passport is considered expired if its expiration year is before `reference_year`,
+/// so passports expiring during the reference year are still valid.
+///
+/// Findings about several passports come first, ordered by the first passport involved,
+/// followed by findings about single passports in the order of the batch
+pub fn analyze_batch(passports: &[Passport], reference_year: u32) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let mut by_passport_id: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut by_fields_except_cid: HashMap<_, Vec<usize>> = HashMap::new();
+    for (idx, passport) in passports.iter().enumerate() {
+        by_passport_id
+            .entry(passport.passport_id())
+            .or_default()
+            .push(idx);
+        by_fields_except_cid
+            .entry(fields_except_country_id(passport))
+            .or_default()
+            .push(idx);
+    }
+
+    let mut groups: Vec<Finding> = by_passport_id
+        .into_iter()
+        .filter(|(_pid, indices)| indices.len() > 1)
+        .map(|(pid, indices)| Finding::DuplicatePassportId {
+            passport_id: pid.to_owned(),
+            indices,
+        })
+        .chain(
+            by_fields_except_cid
+                .into_values()
+                .filter(|indices| {
+                    let first_cid = passports[indices[0]].country_id();
+                    indices
+                        .iter()
+                        .any(|&idx| passports[idx].country_id() != first_cid)
+                })
+                .map(|indices| Finding::DifferentCountryIdOnly { indices }),
+        )
+        .collect();
+    groups.sort_by_key(|f| {
+        (
+            first_index(f),
+            !matches!(f, Finding::DuplicatePassportId { .. }),
+        )
+    });
+    findings.append(&mut groups);
+
+    for (index, passport) in passports.iter().enumerate() {
+        if passport.issue_year() > passport.expiration_year() {
+            findings.push(Finding::IssuedAfterExpiration {
+                index,
+                issue_year: passport.issue_year(),
+                expiration_year: passport.expiration_year(),
+            });
+        }
+
+        if passport.expiration_year() < reference_year {
+            findings.push(Finding::Expired {
+                index,
+                expiration_year: passport.expiration_year(),
+                reference_year,
+            });
+        }
+    }
+
+    findings
+}
+
+fn first_index(finding: &Finding) -> usize {
+    match finding {
+        Finding::DuplicatePassportId { indices, .. } => indices[0],
+        Finding::DifferentCountryIdOnly { indices } => indices[0],
+        Finding::IssuedAfterExpiration { index, .. } => *index,
+        Finding::Expired { index, .. } => *index,
+    }
+}
+
+/// Every field of the passport except the country id, in a form that can be hashed
+fn fields_except_country_id(passport: &Passport) -> (u32, u32, u32, u64, &str, &str, &str) {
+    (
+        passport.birth_year(),
+        passport.issue_year(),
+        passport.expiration_year(),
+        passport.height().micrometres(),
+        passport.hair_color(),
+        passport.eye_color().abbr(),
+        passport.passport_id(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passport::{schema::PassportSchema, RawPassport};
+
+    #[test]
+    fn analyze_batch_test() {
+        // a schema without any rules, so that inconsistent passports can be created
+        let schema: PassportSchema = "".parse().unwrap();
+        let passports: Vec<Passport> = vec![
+            "byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704",
+            "byr:1989 iyr:2014 eyr:2029 hgt:165cm hcl:#a97842 ecl:blu pid:896056539 cid:129",
+            "byr:1989 iyr:2014 eyr:2029 hgt:1650mm hcl:#a97842 ecl:blu pid:896056539 cid:88",
+            "byr:2001 iyr:2015 eyr:2022 hgt:164cm hcl:#888785 ecl:hzl pid:545766238",
+            "byr:1944 iyr:2021 eyr:2020 hgt:158cm hcl:#b6652a ecl:blu pid:093154719",
+        ]
+        .into_iter()
+        .map(|r| {
+            RawPassport::new(r.split(' ').map(|e| e.to_owned()).collect())
+                .to_passport_with(&schema)
+                .unwrap()
+        })
+        .collect();
+
+        assert_eq!(
+            analyze_batch(&passports, 2021),
+            vec![
+                Finding::DuplicatePassportId {
+                    passport_id: "896056539".to_string(),
+                    indices: vec![1, 2]
+                },
+                Finding::DifferentCountryIdOnly {
+                    indices: vec![1, 2]
+                },
+                Finding::IssuedAfterExpiration {
+                    index: 4,
+                    issue_year: 2021,
+                    expiration_year: 2020
+                },
+                Finding::Expired {
+                    index: 4,
+                    expiration_year: 2020,
+                    reference_year: 2021
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    fmt::Display,
+    io::{self, Read, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{consts, Passport, RawPassport, Violation};
+
+/// Writes passports in the batch format, one line per passport with an empty line between them
+///
+/// The output can be read back with `read_valid_passports_from_file`
+pub fn write_batch<W: Write>(mut writer: W, passports: &[Passport]) -> io::Result<()> {
+    for (idx, passport) in passports.iter().enumerate() {
+        if idx > 0 {
+            writeln!(writer)?;
+        }
+        writeln!(writer, "{}", passport)?;
+    }
+
+    Ok(())
+}
+
+/// Writes passports as a JSON array, with one object per passport
+pub fn write_json<W: Write>(writer: W, passports: &[Passport]) -> Result<(), FormatError> {
+    let records: Vec<_> = passports.iter().map(PassportRecord::from).collect();
+    serde_json::to_writer_pretty(writer, &records).map_err(FormatError::Json)
+}
+
+/// Reads a JSON array of passports, validating each one like `validate_passports` does
+pub fn read_json<R: Read>(reader: R) -> Result<Vec<Result<Passport, Vec<Violation>>>, FormatError> {
+    let records: Vec<PassportRecord> =
+        serde_json::from_reader(reader).map_err(FormatError::Json)?;
+    Ok(records.into_iter().map(|r| r.to_passport()).collect())
+}
+
+/// Writes passports as CSV, with a header row of field abbreviations
+pub fn write_csv<W: Write>(writer: W, passports: &[Passport]) -> Result<(), FormatError> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for passport in passports {
+        writer
+            .serialize(PassportRecord::from(passport))
+            .map_err(FormatError::Csv)?;
+    }
+    writer.flush().map_err(FormatError::Io)
+}
+
+/// Reads passports from CSV with a header row, validating each one like `validate_passports` does
+pub fn read_csv<R: Read>(reader: R) -> Result<Vec<Result<Passport, Vec<Violation>>>, FormatError> {
+    csv::Reader::from_reader(reader)
+        .deserialize()
+        .map(|r| {
+            r.map(|record: PassportRecord| record.to_passport())
+                .map_err(FormatError::Csv)
+        })
+        .collect()
+}
+
+/// The fields of a passport as they are stored in JSON and CSV, named by their abbreviations
+#[derive(Debug, Serialize, Deserialize)]
+struct PassportRecord {
+    byr: u32,
+    iyr: u32,
+    eyr: u32,
+    hgt: String,
+    hcl: String,
+    ecl: String,
+    pid: String,
+    cid: Option<String>,
+}
+
+impl PassportRecord {
+    fn to_passport(&self) -> Result<Passport, Vec<Violation>> {
+        use consts::*;
+        let mut entries = vec![
+            format!("{}:{}", BIRTH_YEAR_ABBR, self.byr),
+            format!("{}:{}", ISSUE_YEAR_ABBR, self.iyr),
+            format!("{}:{}", EXPIRATION_YEAR_ABBR, self.eyr),
+            format!("{}:{}", HEIGHT_ABBR, self.hgt),
+            format!("{}:{}", HAIR_COLOR_ABBR, self.hcl),
+            format!("{}:{}", EYE_COLOR_ABBR, self.ecl),
+            format!("{}:{}", PASSPORT_ID_ABBR, self.pid),
+        ];
+        if let Some(cid) = &self.cid {
+            entries.push(format!("{}:{}", COUNTRY_ID_ABBR, cid));
+        }
+
+        RawPassport::new(entries).to_passport()
+    }
+}
+
+impl From<&Passport> for PassportRecord {
+    fn from(passport: &Passport) -> Self {
+        Self {
+            byr: passport.birth_year(),
+            iyr: passport.issue_year(),
+            eyr: passport.expiration_year(),
+            hgt: passport.height().to_string(),
+            hcl: passport.hair_color().to_owned(),
+            ecl: passport.eye_color().to_string(),
+            pid: passport.passport_id().to_owned(),
+            cid: passport.country_id().map(|cid| cid.to_owned()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FormatError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Csv(csv::Error),
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatError::Io(e) => write!(f, "io error: {}", e),
+            FormatError::Json(e) => write!(f, "invalid json: {}", e),
+            FormatError::Csv(e) => write!(f, "invalid csv: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passport::reader::PassportReader;
+
+    fn example_passports() -> Vec<Passport> {
+        vec![
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f",
+            "eyr:2029 ecl:blu cid:129 byr:1989 iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm",
+        ]
+        .into_iter()
+        .map(|r| {
+            RawPassport::new(r.split(' ').map(|e| e.to_owned()).collect())
+                .to_passport()
+                .unwrap()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn batch_round_trip_test() {
+        let passports = example_passports();
+
+        assert_eq!(
+            passports[1].to_string(),
+            "byr:1989 iyr:2014 eyr:2029 hgt:165cm hcl:#a97842 ecl:blu pid:896056539 cid:129"
+        );
+
+        let mut batch = Vec::new();
+        write_batch(&mut batch, &passports).unwrap();
+        let read_back: Vec<Passport> = PassportReader::new(&batch[..])
+            .map(|r| r.unwrap().to_passport().unwrap())
+            .collect();
+
+        assert_eq!(read_back, passports);
+    }
+
+    #[test]
+    fn json_and_csv_round_trip_test() {
+        let passports = example_passports();
+
+        let mut json = Vec::new();
+        write_json(&mut json, &passports).unwrap();
+        let read_back: Vec<Passport> = read_json(json.as_slice())
+            .unwrap()
+            .into_iter()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(read_back, passports);
+
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &passports).unwrap();
+        assert!(String::from_utf8_lossy(&csv).starts_with("byr,iyr,eyr,hgt,hcl,ecl,pid,cid\n"));
+        let read_back: Vec<Passport> = read_csv(csv.as_slice())
+            .unwrap()
+            .into_iter()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(read_back, passports);
+    }
+
+    #[test]
+    fn import_invalid_test() {
+        let csv = "byr,iyr,eyr,hgt,hcl,ecl,pid,cid\n1926,2018,1972,170,#18171d,amb,186cm,100\n";
+
+        let results = read_csv(csv.as_bytes()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0]
+            .as_ref()
+            .unwrap_err()
+            .contains(&Violation::MalformedPassportId("186cm".to_string())));
+    }
+}
@@ -1,4 +1,12 @@
-use std::{fmt::Display, num::ParseIntError, str::FromStr};
+pub mod analysis;
+pub mod format;
+pub mod reader;
+pub mod schema;
+
+use std::{convert::TryFrom, fmt::Display, num::ParseIntError, str::FromStr};
+
+use reader::PassportReader;
+use schema::PassportSchema;
 
 mod consts {
     pub const BIRTH_YEAR_ABBR: &str = "byr";
@@ -68,7 +76,7 @@ impl Passport {
         }
     }
 
-    fn from_fields(fields: Vec<Field>) -> Result<Self, ()> {
+    fn from_fields(fields: Vec<Field>) -> Result<Self, Vec<Violation>> {
         let mut builder = PassportBuilder::new();
         for field in fields {
             match field {
@@ -87,6 +95,37 @@ impl Passport {
     }
 }
 
+impl Display for Passport {
+    /// Writes the passport as a single line of `key:value` entries, as it would appear in a batch file
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use consts::*;
+        write!(
+            f,
+            "{}:{} {}:{} {}:{} {}:{} {}:{} {}:{} {}:{}",
+            BIRTH_YEAR_ABBR,
+            self.birth_year(),
+            ISSUE_YEAR_ABBR,
+            self.issue_year(),
+            EXPIRATION_YEAR_ABBR,
+            self.expiration_year(),
+            HEIGHT_ABBR,
+            self.height(),
+            HAIR_COLOR_ABBR,
+            self.hair_color(),
+            EYE_COLOR_ABBR,
+            self.eye_color(),
+            PASSPORT_ID_ABBR,
+            self.passport_id()
+        )?;
+
+        if let Some(cid) = self.country_id() {
+            write!(f, " {}:{}", COUNTRY_ID_ABBR, cid)?;
+        }
+
+        Ok(())
+    }
+}
+
 struct PassportBuilder {
     birth_year: Option<BirthYear>,
     issue_year: Option<IssueYear>,
@@ -112,7 +151,17 @@ impl PassportBuilder {
         }
     }
 
-    fn build(self) -> Result<Passport, ()> {
+    fn build(self) -> Result<Passport, Vec<Violation>> {
+        let present = [
+            (FieldKind::BirthYear, self.birth_year.is_some()),
+            (FieldKind::IssueYear, self.issue_year.is_some()),
+            (FieldKind::ExpirationYear, self.expiration_year.is_some()),
+            (FieldKind::Height, self.height.is_some()),
+            (FieldKind::HairColor, self.hair_color.is_some()),
+            (FieldKind::EyeColor, self.eye_color.is_some()),
+            (FieldKind::PassportId, self.passport_id.is_some()),
+        ];
+
         if let (
             Some(birth_year),
             Some(issue_year),
@@ -143,7 +192,11 @@ impl PassportBuilder {
                 country_id,
             })
         } else {
-            Err(())
+            Err(present
+                .iter()
+                .filter(|(_kind, is_present)| !is_present)
+                .map(|&(kind, _)| Violation::MissingField(kind))
+                .collect())
         }
     }
 
@@ -192,18 +245,266 @@ enum Field {
     CountryId(CountryId),
 }
 
+impl Field {
+    /// Parses the value of a field, without checking it against the rules of any schema
+    fn parse(kind: FieldKind, value: &str) -> Result<Self, Violation> {
+        fn parse_year(kind: FieldKind, value: &str) -> Result<u32, Violation> {
+            value.parse().map_err(|_| Violation::InvalidYear {
+                field: kind,
+                value: value.to_owned(),
+            })
+        }
+
+        Ok(match kind {
+            FieldKind::BirthYear => Field::BirthYear(BirthYear(parse_year(kind, value)?)),
+            FieldKind::IssueYear => Field::IssueYear(IssueYear(parse_year(kind, value)?)),
+            FieldKind::ExpirationYear => {
+                Field::ExpirationYear(ExpirationYear(parse_year(kind, value)?))
+            }
+            FieldKind::Height => Field::Height(value.parse().map_err(|e| match e {
+                ParseHeightError::InvalidUnit(_) => Violation::InvalidHeightUnit(value.to_owned()),
+                ParseHeightError::InvalidValue(_) | ParseHeightError::OutOfRange => {
+                    Violation::InvalidHeightValue(value.to_owned())
+                }
+            })?),
+            FieldKind::HairColor => Field::HairColor(HairColor(value.to_owned())),
+            FieldKind::EyeColor => Field::EyeColor(
+                value
+                    .parse()
+                    .map_err(|_| Violation::InvalidEyeColor(value.to_owned()))?,
+            ),
+            FieldKind::PassportId => Field::PassportId(PassportId(value.to_owned())),
+            FieldKind::CountryId => Field::CountryId(CountryId(value.to_owned())),
+        })
+    }
+
+    fn kind(&self) -> FieldKind {
+        match self {
+            Field::BirthYear(_) => FieldKind::BirthYear,
+            Field::IssueYear(_) => FieldKind::IssueYear,
+            Field::ExpirationYear(_) => FieldKind::ExpirationYear,
+            Field::Height(_) => FieldKind::Height,
+            Field::HairColor(_) => FieldKind::HairColor,
+            Field::EyeColor(_) => FieldKind::EyeColor,
+            Field::PassportId(_) => FieldKind::PassportId,
+            Field::CountryId(_) => FieldKind::CountryId,
+        }
+    }
+}
+
+/// The kinds of fields that can appear on a passport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldKind {
+    BirthYear,
+    IssueYear,
+    ExpirationYear,
+    Height,
+    HairColor,
+    EyeColor,
+    PassportId,
+    CountryId,
+}
+
+impl FieldKind {
+    /// The fields that every passport must contain
+    pub const REQUIRED: [FieldKind; 7] = [
+        FieldKind::BirthYear,
+        FieldKind::IssueYear,
+        FieldKind::ExpirationYear,
+        FieldKind::Height,
+        FieldKind::HairColor,
+        FieldKind::EyeColor,
+        FieldKind::PassportId,
+    ];
+
+    /// The abbreviation used as the key of the field in a passport batch file
+    pub fn abbr(&self) -> &'static str {
+        use consts::*;
+        match self {
+            FieldKind::BirthYear => BIRTH_YEAR_ABBR,
+            FieldKind::IssueYear => ISSUE_YEAR_ABBR,
+            FieldKind::ExpirationYear => EXPIRATION_YEAR_ABBR,
+            FieldKind::Height => HEIGHT_ABBR,
+            FieldKind::HairColor => HAIR_COLOR_ABBR,
+            FieldKind::EyeColor => EYE_COLOR_ABBR,
+            FieldKind::PassportId => PASSPORT_ID_ABBR,
+            FieldKind::CountryId => COUNTRY_ID_ABBR,
+        }
+    }
+}
+
+impl FromStr for FieldKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use consts::*;
+        Ok(match s {
+            BIRTH_YEAR_ABBR => FieldKind::BirthYear,
+            ISSUE_YEAR_ABBR => FieldKind::IssueYear,
+            EXPIRATION_YEAR_ABBR => FieldKind::ExpirationYear,
+            HEIGHT_ABBR => FieldKind::Height,
+            HAIR_COLOR_ABBR => FieldKind::HairColor,
+            EYE_COLOR_ABBR => FieldKind::EyeColor,
+            PASSPORT_ID_ABBR => FieldKind::PassportId,
+            COUNTRY_ID_ABBR => FieldKind::CountryId,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl Display for FieldKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.abbr())
+    }
+}
+
+/// A reason why a passport record does not make up a valid passport
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A required field is not present in the record
+    MissingField(FieldKind),
+    /// An entry in the record is not of the form `key:value`
+    MalformedEntry(String),
+    /// An entry has a key that is not a passport field
+    UnknownField(String),
+    /// A year field has a value that is not a number
+    InvalidYear { field: FieldKind, value: String },
+    /// A year field has a value outside of its allowed range
+    YearOutOfRange {
+        field: FieldKind,
+        year: u32,
+        min: u32,
+        max: u32,
+    },
+    /// A height does not end with a unit that is allowed
+    InvalidHeightUnit(String),
+    /// A height does not start with a number
+    InvalidHeightValue(String),
+    /// A height is outside of the allowed range
+    HeightOutOfRange(Height),
+    /// A hair color is not a valid hex color, "#" followed by 6 characters 0-9 or a-f by default
+    InvalidHairColor(String),
+    /// An eye color is not one of the allowed abbreviations
+    InvalidEyeColor(String),
+    /// A passport id does not have the allowed format, exactly 9 digits by default
+    MalformedPassportId(String),
+    /// A country id does not have the allowed format
+    InvalidCountryId(String),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::MissingField(kind) => write!(f, "missing field '{}'", kind),
+            Violation::MalformedEntry(e) => write!(f, "malformed entry '{}'", e),
+            Violation::UnknownField(k) => write!(f, "unknown field '{}'", k),
+            Violation::InvalidYear { field, value } => {
+                write!(f, "'{}' is not a valid year for '{}'", value, field)
+            }
+            Violation::YearOutOfRange {
+                field,
+                year,
+                min,
+                max,
+            } => write!(
+                f,
+                "{} is outside of the range {}-{} for '{}'",
+                year, min, max, field
+            ),
+            Violation::InvalidHeightUnit(h) => write!(f, "height '{}' has an invalid unit", h),
+            Violation::InvalidHeightValue(h) => write!(f, "height '{}' has an invalid value", h),
+            Violation::HeightOutOfRange(h) => write!(f, "height {} is out of range", h),
+            Violation::InvalidHairColor(c) => write!(f, "'{}' is not a valid hex color", c),
+            Violation::InvalidEyeColor(c) => write!(f, "'{}' is not a valid eye color", c),
+            Violation::MalformedPassportId(pid) => {
+                write!(f, "'{}' is not a valid passport id", pid)
+            }
+            Violation::InvalidCountryId(cid) => write!(f, "'{}' is not a valid country id", cid),
+        }
+    }
+}
+
 /// A measurement of height, with a value and a unit
-#[derive(Debug, Clone, PartialEq, Copy)]
+///
+/// Heights are compared by their length, so `150cm` is equal to `1500mm`
+#[derive(Debug, Clone, Copy)]
 pub struct Height(u32, HeightUnit);
 
 impl Height {
-    fn new(v: u32, u: HeightUnit) -> Result<Self, ()> {
-        // heights are only valid if the unit is "cm" and the value is between 150 and 193
-        // or if the unit is "in" and the value is between 59 and 76
-        match u {
-            HeightUnit::Cm if v >= 150 && v <= 193 => Ok(Self(v, u)),
-            HeightUnit::In if v >= 59 && v <= 76 => Ok(Self(v, u)),
-            _ => Err(()),
+    /// Creates a height of `v` in the unit `u`
+    ///
+    /// For `HeightUnit::FeetInches`, `v` is the total number of inches
+    pub fn new(v: u32, u: HeightUnit) -> Self {
+        Self(v, u)
+    }
+
+    /// Creates a height of `feet` and `inches`
+    ///
+    /// # Returns
+    /// `None` if `inches` is 12 or more, or if the total number of inches doesn't fit in a `u32`
+    pub fn feet_inches(feet: u32, inches: u32) -> Option<Self> {
+        if inches >= 12 {
+            return None;
+        }
+
+        let total = feet.checked_mul(12)?.checked_add(inches)?;
+        Some(Self(total, HeightUnit::FeetInches))
+    }
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    pub fn unit(&self) -> HeightUnit {
+        self.1
+    }
+
+    /// The length of the height in micrometres, which every unit converts to exactly
+    pub fn micrometres(&self) -> u64 {
+        u64::from(self.value()) * self.unit().micrometres()
+    }
+
+    /// The length of the height in millimetres
+    pub fn millimetres(&self) -> f64 {
+        self.micrometres() as f64 / 1000.0
+    }
+
+    /// Converts the height to another unit, rounding to the nearest whole value in that unit
+    ///
+    /// # Returns
+    /// * `None` if the value in the other unit doesn't fit in a `u32`
+    pub fn convert_to(&self, unit: HeightUnit) -> Option<Height> {
+        let per_unit = unit.micrometres();
+        let value = (self.micrometres() + per_unit / 2) / per_unit;
+        Some(Height::new(u32::try_from(value).ok()?, unit))
+    }
+}
+
+impl PartialEq for Height {
+    fn eq(&self, other: &Self) -> bool {
+        self.micrometres() == other.micrometres()
+    }
+}
+
+impl Eq for Height {}
+
+impl PartialOrd for Height {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Height {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.micrometres().cmp(&other.micrometres())
+    }
+}
+
+impl Display for Height {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.unit() {
+            HeightUnit::FeetInches => write!(f, "{}'{}\"", self.value() / 12, self.value() % 12),
+            unit => write!(f, "{}{}", self.value(), unit.abbr()),
         }
     }
 }
@@ -212,16 +513,31 @@ impl FromStr for Height {
     type Err = ParseHeightError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 5'11"
+        if let Some(feet_inches) = s.strip_suffix('"') {
+            let parts: Vec<_> = feet_inches.split('\'').collect();
+            if parts.len() != 2 {
+                return Err(ParseHeightError::InvalidUnit(ParseHeightUnitError::Invalid));
+            }
+
+            let feet: u32 = parts[0].parse().map_err(ParseHeightError::InvalidValue)?;
+            let inches: u32 = parts[1].parse().map_err(ParseHeightError::InvalidValue)?;
+
+            return Height::feet_inches(feet, inches).ok_or(ParseHeightError::OutOfRange);
+        }
+
         // 150cm
-        let unit: HeightUnit = s[s.len() - 2..]
+        let split_at = s.trim_end_matches(|c: char| c.is_ascii_alphabetic()).len();
+
+        let unit: HeightUnit = s[split_at..]
             .parse()
             .map_err(ParseHeightError::InvalidUnit)?;
 
-        let value: u32 = s[..s.len() - 2]
+        let value: u32 = s[..split_at]
             .parse()
             .map_err(ParseHeightError::InvalidValue)?;
 
-        Ok(Height::new(value, unit).map_err(|_| ParseHeightError::InvalidHeight)?)
+        Ok(Height::new(value, unit))
     }
 }
 
@@ -229,7 +545,8 @@ impl FromStr for Height {
 pub enum ParseHeightError {
     InvalidUnit(ParseHeightUnitError),
     InvalidValue(ParseIntError),
-    InvalidHeight,
+    /// Inches of 12 or more, or a height too large to be stored
+    OutOfRange,
 }
 
 impl Display for ParseHeightError {
@@ -237,17 +554,20 @@ impl Display for ParseHeightError {
         let output = match self {
             ParseHeightError::InvalidUnit(e) => format!("invalid unit: '{:?}'", e),
             ParseHeightError::InvalidValue(e) => format!("invalid value: '{:?}'", e),
-            ParseHeightError::InvalidHeight => "invalid height".to_string(),
+            ParseHeightError::OutOfRange => "value out of range".to_owned(),
         };
 
         write!(f, "failed to parse height: {}", output)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
 pub enum HeightUnit {
     Cm,
     In,
+    Mm,
+    /// Feet and inches, like `5'11"`
+    FeetInches,
 }
 
 #[derive(Debug)]
@@ -255,6 +575,28 @@ pub enum ParseHeightUnitError {
     Invalid,
 }
 
+impl HeightUnit {
+    pub fn abbr(&self) -> &'static str {
+        match self {
+            HeightUnit::Cm => "cm",
+            HeightUnit::In => "in",
+            HeightUnit::Mm => "mm",
+            HeightUnit::FeetInches => "ft'in\"",
+        }
+    }
+
+    /// How many micrometres one of the unit is
+    ///
+    /// A height in `FeetInches` counts inches
+    fn micrometres(&self) -> u64 {
+        match self {
+            HeightUnit::Cm => 10_000,
+            HeightUnit::In | HeightUnit::FeetInches => 25_400,
+            HeightUnit::Mm => 1_000,
+        }
+    }
+}
+
 impl FromStr for HeightUnit {
     type Err = ParseHeightUnitError;
 
@@ -262,57 +604,28 @@ impl FromStr for HeightUnit {
         Ok(match s {
             "cm" => HeightUnit::Cm,
             "in" => HeightUnit::In,
+            "mm" => HeightUnit::Mm,
+            "ft'in\"" => HeightUnit::FeetInches,
             _ => return Err(ParseHeightUnitError::Invalid),
         })
     }
 }
 
 impl FromStr for Field {
-    type Err = ();
+    type Err = Violation;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use consts::*;
         let parts: Vec<_> = s.split(':').collect();
 
         if parts.len() != 2 || parts[0].is_empty() || parts[1].is_empty() {
-            return Err(());
+            return Err(Violation::MalformedEntry(s.to_owned()));
         }
 
-        match parts[0] {
-            BIRTH_YEAR_ABBR => {
-                let birth_year: u32 = parts[1].parse().map_err(|_| ())?;
-                Ok(Field::BirthYear(BirthYear::new(birth_year)?))
-            }
-            ISSUE_YEAR_ABBR => {
-                let issue_year: u32 = parts[1].parse().map_err(|_| ())?;
-                Ok(Field::IssueYear(IssueYear::new(issue_year)?))
-            }
-            EXPIRATION_YEAR_ABBR => {
-                let expiration_year: u32 = parts[1].parse().map_err(|_| ())?;
-                Ok(Field::ExpirationYear(ExpirationYear::new(expiration_year)?))
-            }
-            HEIGHT_ABBR => {
-                let height = parts[1].parse().map_err(|_| ())?;
-                Ok(Field::Height(height))
-            }
-            HAIR_COLOR_ABBR => {
-                let hair_color = parts[1].to_owned();
-                Ok(Field::HairColor(HairColor::new(hair_color)?))
-            }
-            EYE_COLOR_ABBR => {
-                let eye_color = parts[1].parse().map_err(|_| ())?;
-                Ok(Field::EyeColor(eye_color))
-            }
-            PASSPORT_ID_ABBR => {
-                let passport_id = parts[1].to_owned();
-                Ok(Field::PassportId(PassportId::new(passport_id)?))
-            }
-            COUNTRY_ID_ABBR => {
-                let country_id = parts[1].to_owned();
-                Ok(Field::CountryId(CountryId::new(country_id)?))
-            }
-            _ => Err(()),
-        }
+        let kind: FieldKind = parts[0]
+            .parse()
+            .map_err(|_| Violation::UnknownField(parts[0].to_owned()))?;
+
+        Field::parse(kind, parts[1])
     }
 }
 
@@ -327,6 +640,27 @@ pub enum EyeColor {
     Other,
 }
 
+impl EyeColor {
+    /// The abbreviation used for the eye color in a passport batch file
+    pub fn abbr(&self) -> &'static str {
+        match self {
+            EyeColor::Amber => consts::AMBER,
+            EyeColor::Blue => consts::BLUE,
+            EyeColor::Brown => consts::BROWN,
+            EyeColor::Gray => consts::GRAY,
+            EyeColor::Green => consts::GREEN,
+            EyeColor::Hazel => consts::HAZEL,
+            EyeColor::Other => consts::OTHER,
+        }
+    }
+}
+
+impl Display for EyeColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.abbr())
+    }
+}
+
 impl FromStr for EyeColor {
     type Err = ();
 
@@ -344,132 +678,173 @@ impl FromStr for EyeColor {
     }
 }
 
+/// Reads the passports in a batch file that have a valid value for every required field
+///
+/// Unlike `validate_passports`, entries that are malformed, unknown or invalid are skipped
+/// instead of making the whole record invalid, so a record is only rejected if a required field is left out.
 pub fn read_valid_passports_from_file(filename: &str) -> Vec<Passport> {
-    let lines: Vec<String> = crate::read_lines_from_file(filename)
+    PassportReader::from_file(filename)
         .unwrap()
-        .map(|l| l.unwrap())
-        .collect();
+        .filter_map(|r| lenient_passport(&r.unwrap()))
+        .collect()
+}
 
-    let mut empty_lines: Vec<usize> = lines
+fn lenient_passport(raw: &RawPassport) -> Option<Passport> {
+    let schema = PassportSchema::standard();
+    let entries = raw
+        .entries()
         .iter()
-        .enumerate()
-        .filter_map(|(idx, content)| if content.is_empty() { Some(idx) } else { None })
+        .filter(|e| schema.check_entry(e).is_ok())
+        .cloned()
         .collect();
 
-    empty_lines.insert(0, 0); // first line
-    empty_lines.push(lines.len());
+    RawPassport::new(entries).to_passport().ok()
+}
 
-    let mut valid_passports = Vec::new();
-    for v in empty_lines.windows(2) {
-        let (mut start, end) = (v[0], v[1]);
+/// Validates every passport record in a batch file
+///
+/// # Returns
+/// * One result per record, in the order they appear in the file
+/// * Each result is either the valid `Passport`, or every `Violation` that made the record invalid
+pub fn validate_passports(filename: &str) -> Vec<Result<Passport, Vec<Violation>>> {
+    validate_passports_with(filename, PassportSchema::standard())
+}
 
-        if start != 0 {
-            start += 1;
-        }
+/// Validates every passport record in a batch file against the rules of `schema`
+pub fn validate_passports_with(
+    filename: &str,
+    schema: &PassportSchema,
+) -> Vec<Result<Passport, Vec<Violation>>> {
+    PassportReader::from_file(filename)
+        .unwrap()
+        .map(|r| r.unwrap().to_passport_with(schema))
+        .collect()
+}
 
-        let mut fields = Vec::new();
-        for l in &lines[start..end] {
-            let mut fields_in_line: Vec<_> = l
-                .split(' ')
-                .filter_map(|p| Field::from_str(p).ok())
-                .collect();
-            fields.append(&mut fields_in_line);
-        }
-        if let Ok(valid_passport) = Passport::from_fields(fields) {
-            valid_passports.push(valid_passport);
-        }
-    }
+/// Reads every record in a batch file without validating any of its fields
+pub fn read_raw_passports_from_file(filename: &str) -> Vec<RawPassport> {
+    PassportReader::from_file(filename)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect()
+}
 
-    valid_passports
+/// How thoroughly a `RawPassport` is checked
+pub enum ValidationMode {
+    /// Only checks that every required field is present, regardless of its value
+    PresenceOnly,
+    /// Checks that every required field is present and that every field has a valid value,
+    /// according to the standard schema
+    Strict,
+    /// Checks the record against the rules of a schema
+    Schema(PassportSchema),
+    /// Checks the record with a user-provided function
+    Custom(Box<CustomValidator>),
 }
 
+/// A function returning every violation it finds in a record
+pub type CustomValidator = dyn Fn(&RawPassport) -> Vec<Violation>;
+
+/// A passport record as it appears in a batch file, with its `key:value` entries left unparsed
 #[derive(Debug, Clone, PartialEq)]
-struct BirthYear(u32);
+pub struct RawPassport {
+    entries: Vec<String>,
+}
 
-impl BirthYear {
-    fn new(v: u32) -> Result<Self, ()> {
-        // birth years are only valid between 1920 and 2002
-        if v >= 1920 && v <= 2002 {
-            Ok(Self(v))
-        } else {
-            Err(())
-        }
+impl RawPassport {
+    pub fn new(entries: Vec<String>) -> Self {
+        Self { entries }
+    }
+
+    /// The `key:value` entries of the record, in the order they appeared
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// The unparsed value of the field with the given key, if the record contains it
+    ///
+    /// If the key appears several times, the last value is returned
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .rev()
+            .filter_map(|e| {
+                let mut parts = e.splitn(2, ':');
+                match (parts.next(), parts.next()) {
+                    (Some(k), Some(v)) if k == key => Some(v),
+                    _ => None,
+                }
+            })
+            .next()
     }
-}
 
-#[derive(Debug, Clone, PartialEq)]
-struct IssueYear(u32);
+    pub fn validate(&self, mode: &ValidationMode) -> Result<(), Vec<Violation>> {
+        let violations = match mode {
+            ValidationMode::PresenceOnly => self.missing_fields(),
+            ValidationMode::Strict => PassportSchema::standard().validate(self),
+            ValidationMode::Schema(schema) => schema.validate(self),
+            ValidationMode::Custom(validator) => validator(self),
+        };
 
-impl IssueYear {
-    fn new(v: u32) -> Result<Self, ()> {
-        // issue years are only valid between 2010 and 2020
-        if v >= 2010 && v <= 2020 {
-            Ok(Self(v))
+        if violations.is_empty() {
+            Ok(())
         } else {
-            Err(())
+            Err(violations)
         }
     }
-}
 
-#[derive(Debug, Clone, PartialEq)]
-struct ExpirationYear(u32);
+    fn missing_fields(&self) -> Vec<Violation> {
+        FieldKind::REQUIRED
+            .iter()
+            .filter(|kind| self.get(kind.abbr()).filter(|v| !v.is_empty()).is_none())
+            .map(|&kind| Violation::MissingField(kind))
+            .collect()
+    }
 
-impl ExpirationYear {
-    fn new(v: u32) -> Result<Self, ()> {
-        // expiration years are only valid between 2020 and 2030
-        if v >= 2020 && v <= 2030 {
-            Ok(Self(v))
-        } else {
-            Err(())
+    /// Parses and strictly validates every field of the record
+    pub fn to_passport(&self) -> Result<Passport, Vec<Violation>> {
+        self.to_passport_with(PassportSchema::standard())
+    }
+
+    /// Parses every field of the record after validating it against `schema`
+    ///
+    /// This can fail even if the record follows the schema, if the schema does not require
+    /// every field of a `Passport`, or allows eye colors that `EyeColor` does not know about
+    pub fn to_passport_with(&self, schema: &PassportSchema) -> Result<Passport, Vec<Violation>> {
+        let violations = schema.validate(self);
+        if !violations.is_empty() {
+            return Err(violations);
         }
+
+        let fields = self
+            .entries()
+            .iter()
+            .map(|e| Field::from_str(e))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|v| vec![v])?;
+
+        Passport::from_fields(fields)
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-struct HairColor(String);
+struct BirthYear(u32);
 
-impl HairColor {
-    fn new(s: String) -> Result<Self, ()> {
-        fn is_hexadecimal(c: char) -> bool {
-            c.is_numeric() || {
-                let ascii_idx = c as u8 - b'a';
-                ascii_idx <= 5
-            }
-        }
+#[derive(Debug, Clone, PartialEq)]
+struct IssueYear(u32);
 
-        // hair color is only valid if it is a string beginning with "#", followed by exactly 6 characters 0-9 or a-f
-        if s.len() != 7 || !s.starts_with('#') || !s[1..].chars().all(is_hexadecimal) {
-            Err(())
-        } else {
-            Ok(Self(s))
-        }
-    }
-}
+#[derive(Debug, Clone, PartialEq)]
+struct ExpirationYear(u32);
 
 #[derive(Debug, Clone, PartialEq)]
-struct PassportId(String);
+struct HairColor(String);
 
-impl PassportId {
-    fn new(s: String) -> Result<Self, ()> {
-        // passport ids are only valid if the length is 9 and all characters in the string are numeric
-        if s.len() != 9 || !s.chars().all(|c| c.is_numeric()) {
-            Err(())
-        } else {
-            Ok(Self(s))
-        }
-    }
-}
+#[derive(Debug, Clone, PartialEq)]
+struct PassportId(String);
 
 #[derive(Debug, Clone, PartialEq)]
 struct CountryId(String);
 
-impl CountryId {
-    fn new(s: String) -> Result<Self, ()> {
-        // country ids are always valid (for now)
-        Ok(Self(s))
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -477,7 +852,41 @@ mod tests {
     #[test]
     fn parse_height_test() {
         use HeightUnit::*;
-        assert_eq!(Height::from_str("150cm").unwrap(), Height(150, Cm))
+        assert_eq!(Height::from_str("150cm").unwrap(), Height(150, Cm));
+        assert_eq!(Height::from_str("1800mm").unwrap(), Height(1800, Mm));
+
+        let height = Height::from_str("5'11\"").unwrap();
+        assert_eq!(height, Height(71, In));
+        assert_eq!(height.to_string(), "5'11\"");
+
+        assert!(matches!(
+            Height::from_str("5'12\""),
+            Err(ParseHeightError::OutOfRange)
+        ));
+        assert!(matches!(
+            Height::from_str("400000000'0\""),
+            Err(ParseHeightError::OutOfRange)
+        ));
+    }
+
+    #[test]
+    fn convert_height_test() {
+        use HeightUnit::*;
+        let height = Height::new(74, In);
+
+        assert_eq!(height.micrometres(), 1_879_600);
+        assert_eq!(height.millimetres(), 1879.6);
+        assert_eq!(height.convert_to(Cm), Some(Height::new(188, Cm)));
+        assert_eq!(height.convert_to(Mm), Some(Height::new(1880, Mm)));
+        assert_eq!(height.convert_to(FeetInches).unwrap().to_string(), "6'2\"");
+
+        let tallest = Height::new(u32::MAX, In);
+        assert_eq!(tallest.convert_to(Mm), None);
+        assert_eq!(tallest.convert_to(Cm), None);
+        assert_eq!(tallest.convert_to(FeetInches), Some(tallest));
+
+        assert!(Height::new(150, Cm) > Height::new(59, In));
+        assert!(Height::new(1499, Mm) < Height::new(150, Cm));
     }
 
     #[test]
@@ -502,4 +911,91 @@ mod tests {
             Field::IssueYear(IssueYear(2017))
         );
     }
+
+    #[test]
+    fn validate_record_test() {
+        let valid =
+            raw_passport("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f");
+        assert!(valid.to_passport().is_ok());
+
+        let invalid =
+            raw_passport("eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018");
+        assert_eq!(
+            invalid.to_passport().unwrap_err(),
+            vec![
+                Violation::YearOutOfRange {
+                    field: FieldKind::ExpirationYear,
+                    year: 1972,
+                    min: 2020,
+                    max: 2030
+                },
+                Violation::InvalidHeightUnit("170".to_string()),
+                Violation::MalformedPassportId("186cm".to_string()),
+                Violation::MissingField(FieldKind::BirthYear),
+            ]
+        );
+    }
+
+    #[test]
+    fn lenient_passport_test() {
+        let raw = raw_passport(
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f xyz:1 junk cid:abc",
+        );
+        assert!(raw.to_passport().is_err());
+        assert_eq!(
+            lenient_passport(&raw),
+            Some(
+                raw_passport(
+                    "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f cid:abc"
+                )
+                .to_passport()
+                .unwrap()
+            )
+        );
+
+        // an invalid value for a required field still leaves the field out
+        let raw = raw_passport(
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1900 hcl:#623a2f xyz:1",
+        );
+        assert_eq!(lenient_passport(&raw), None);
+    }
+
+    #[test]
+    fn validation_mode_test() {
+        let raw = raw_passport(
+            "eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926",
+        );
+        assert_eq!(raw.get("hgt"), Some("170"));
+
+        assert!(raw.validate(&ValidationMode::PresenceOnly).is_ok());
+        assert!(raw.validate(&ValidationMode::Strict).is_err());
+
+        let no_cid = ValidationMode::Custom(Box::new(|raw: &RawPassport| {
+            raw.get("cid")
+                .map(|cid| vec![Violation::UnknownField(cid.to_owned())])
+                .unwrap_or_default()
+        }));
+        assert_eq!(
+            raw.validate(&no_cid).unwrap_err(),
+            vec![Violation::UnknownField("100".to_string())]
+        );
+
+        let missing = raw_passport("hcl:#cfa07d eyr:2025 pid:166559648 iyr:2011 ecl:brn hgt:59in");
+        assert_eq!(
+            missing.validate(&ValidationMode::PresenceOnly).unwrap_err(),
+            vec![Violation::MissingField(FieldKind::BirthYear)]
+        );
+
+        let oversized = raw_passport(
+            "hgt:400000000'0\" hcl:#cfa07d eyr:2025 pid:166559648 iyr:2011 ecl:brn byr:1980",
+        );
+        assert_eq!(
+            oversized.validate(&ValidationMode::Strict).unwrap_err(),
+            vec![Violation::InvalidHeightValue("400000000'0\"".to_string())]
+        );
+    }
+
+    fn raw_passport(record: &str) -> RawPassport {
+        RawPassport::new(record.split(' ').map(|e| e.to_owned()).collect())
+    }
 }
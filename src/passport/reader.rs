@@ -0,0 +1,93 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use super::RawPassport;
+
+/// Reads passport records one at a time from a batch, such as a file, stdin or an in-memory buffer
+///
+/// Records are separated by blank lines, and only the record being read is kept in memory.
+/// Both LF and CRLF line endings are accepted, and trailing whitespace is ignored
+pub struct PassportReader<R> {
+    reader: R,
+    line: String,
+}
+
+impl<R: BufRead> PassportReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+        }
+    }
+}
+
+impl PassportReader<BufReader<File>> {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(Self::new(BufReader::new(file)))
+    }
+}
+
+impl<R: BufRead> Iterator for PassportReader<R> {
+    type Item = io::Result<RawPassport>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut entries = Vec::new();
+
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+
+            let line = self.line.trim_end();
+            if line.is_empty() {
+                if entries.is_empty() {
+                    // several blank lines in a row, or blank lines before the first record
+                    continue;
+                }
+                break;
+            }
+
+            entries.extend(line.split_whitespace().map(|e| e.to_owned()));
+        }
+
+        if entries.is_empty() {
+            None
+        } else {
+            Some(Ok(RawPassport::new(entries)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_records_test() {
+        let batch = "\r\necl:gry pid:860033327 eyr:2020  \r\nbyr:1937\r\n\r\n\r\n  \r\niyr:2013 ecl:amb\r\n";
+
+        let records: Vec<RawPassport> = PassportReader::new(batch.as_bytes())
+            .map(|r| r.unwrap())
+            .collect();
+
+        assert_eq!(
+            records,
+            vec![
+                RawPassport::new(vec![
+                    "ecl:gry".to_string(),
+                    "pid:860033327".to_string(),
+                    "eyr:2020".to_string(),
+                    "byr:1937".to_string()
+                ]),
+                RawPassport::new(vec!["iyr:2013".to_string(), "ecl:amb".to_string()]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,386 @@
+use std::{
+    collections::HashMap, fmt::Display, io, ops::RangeInclusive, path::Path, str::FromStr,
+    sync::OnceLock,
+};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use super::{Field, FieldKind, HeightUnit, RawPassport, Violation};
+
+/// The rules of the passport office, used when no other schema is given
+const STANDARD_SCHEMA: &str = include_str!("default_schema.toml");
+
+/// The rules a passport record must follow to be valid
+///
+/// A schema is written in TOML, listing the required fields and the rules for the value of each field:
+/// ```toml
+/// required = ["byr", "hgt"]
+///
+/// [fields.byr]
+/// range = [1920, 2002]
+///
+/// [fields.hgt]
+/// units = ["cm", "in"]
+/// range_mm = [1500, 1930.4]
+///
+/// [fields.ecl]
+/// one_of = ["amb", "blu"]
+///
+/// [fields.pid]
+/// pattern = "[0-9]{9}"
+/// ```
+/// * `range` applies to year fields, and is inclusive
+/// * `units` applies to heights, listing the units a height may be written in
+/// * `range_mm` applies to heights, and is an inclusive range in millimetres regardless of the unit
+/// * `pattern` and `one_of` apply to the other fields, and a value must satisfy both if both are given
+#[derive(Debug, Clone)]
+pub struct PassportSchema {
+    required: Vec<FieldKind>,
+    rules: HashMap<FieldKind, Rule>,
+}
+
+impl PassportSchema {
+    /// The schema with the rules of the passport office, as shipped in `default_schema.toml`
+    pub fn standard() -> &'static PassportSchema {
+        static STANDARD: OnceLock<PassportSchema> = OnceLock::new();
+
+        STANDARD.get_or_init(|| {
+            STANDARD_SCHEMA
+                .parse()
+                .expect("the standard schema should be valid")
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, SchemaError> {
+        let contents = std::fs::read_to_string(path).map_err(SchemaError::Io)?;
+        contents.parse()
+    }
+
+    pub fn required(&self) -> &[FieldKind] {
+        &self.required
+    }
+
+    /// Returns every violation of the schema in `raw`
+    pub fn validate(&self, raw: &RawPassport) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut present: Vec<FieldKind> = Vec::new();
+
+        for entry in raw.entries() {
+            if let Some(kind) = entry.split(':').next().and_then(|k| k.parse().ok()) {
+                present.push(kind);
+            }
+
+            if let Err(violation) = self.check_entry(entry) {
+                violations.push(violation);
+            }
+        }
+
+        // fields that were present but invalid have already been reported
+        violations.extend(
+            self.required
+                .iter()
+                .filter(|kind| !present.contains(kind))
+                .map(|&kind| Violation::MissingField(kind)),
+        );
+
+        violations
+    }
+
+    /// Parses a single `key:value` entry and checks its value, regardless of the rest of the record
+    pub fn check_entry(&self, entry: &str) -> Result<(), Violation> {
+        Field::from_str(entry).and_then(|f| self.check(&f))
+    }
+
+    fn check(&self, field: &Field) -> Result<(), Violation> {
+        let kind = field.kind();
+        let rule = match self.rules.get(&kind) {
+            Some(rule) => rule,
+            None => return Ok(()),
+        };
+
+        match field {
+            Field::BirthYear(super::BirthYear(year))
+            | Field::IssueYear(super::IssueYear(year))
+            | Field::ExpirationYear(super::ExpirationYear(year)) => match &rule.range {
+                Some(range) if !range.contains(year) => Err(Violation::YearOutOfRange {
+                    field: kind,
+                    year: *year,
+                    min: *range.start(),
+                    max: *range.end(),
+                }),
+                _ => Ok(()),
+            },
+            Field::Height(height) => {
+                if let Some(units) = &rule.units {
+                    if !units.contains(&height.unit()) {
+                        return Err(Violation::InvalidHeightUnit(height.to_string()));
+                    }
+                }
+
+                match &rule.height_range {
+                    Some(range) if !range.contains(&height.micrometres()) => {
+                        Err(Violation::HeightOutOfRange(*height))
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Field::HairColor(super::HairColor(value))
+            | Field::PassportId(super::PassportId(value))
+            | Field::CountryId(super::CountryId(value)) => rule.check_text(kind, value),
+            Field::EyeColor(ec) => rule.check_text(kind, ec.abbr()),
+        }
+    }
+}
+
+impl Default for PassportSchema {
+    fn default() -> Self {
+        Self::standard().clone()
+    }
+}
+
+impl FromStr for PassportSchema {
+    type Err = SchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let file: SchemaFile = toml::from_str(s).map_err(SchemaError::Toml)?;
+
+        let required = file
+            .required
+            .iter()
+            .map(|k| parse_kind(k))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut rules = HashMap::new();
+        for (key, rule_file) in file.fields {
+            let kind = parse_kind(&key)?;
+            rules.insert(kind, Rule::new(kind, rule_file)?);
+        }
+
+        Ok(Self { required, rules })
+    }
+}
+
+fn parse_kind(key: &str) -> Result<FieldKind, SchemaError> {
+    key.parse()
+        .map_err(|_| SchemaError::UnknownField(key.to_owned()))
+}
+
+#[derive(Debug, Clone, Default)]
+struct Rule {
+    range: Option<RangeInclusive<u32>>,
+    units: Option<Vec<HeightUnit>>,
+    /// The allowed heights in micrometres
+    height_range: Option<RangeInclusive<u64>>,
+    pattern: Option<Regex>,
+    one_of: Option<Vec<String>>,
+}
+
+impl Rule {
+    fn new(kind: FieldKind, file: RuleFile) -> Result<Self, SchemaError> {
+        let is_year = matches!(
+            kind,
+            FieldKind::BirthYear | FieldKind::IssueYear | FieldKind::ExpirationYear
+        );
+        let is_height = kind == FieldKind::Height;
+        let is_text = !is_year && !is_height;
+
+        let unsupported = |rule| Err(SchemaError::UnsupportedRule { field: kind, rule });
+
+        let mut rule = Rule::default();
+
+        if let Some((min, max)) = file.range {
+            if !is_year {
+                return unsupported("range");
+            }
+            rule.range = Some(min..=max);
+        }
+
+        if let Some(units) = file.units {
+            if !is_height {
+                return unsupported("units");
+            }
+            let units = units
+                .iter()
+                .map(|u| u.parse().map_err(|_| SchemaError::UnknownUnit(u.clone())))
+                .collect::<Result<_, _>>()?;
+            rule.units = Some(units);
+        }
+
+        if let Some((min, max)) = file.range_mm {
+            if !is_height {
+                return unsupported("range_mm");
+            }
+            let to_micrometres = |mm: f64| (mm * 1000.0).round() as u64;
+            rule.height_range = Some(to_micrometres(min)..=to_micrometres(max));
+        }
+
+        if let Some(pattern) = file.pattern {
+            if !is_text {
+                return unsupported("pattern");
+            }
+            // patterns must match the whole value, not just a part of it
+            let regex = Regex::new(&format!("^(?:{})$", pattern))
+                .map_err(|error| SchemaError::InvalidPattern { field: kind, error })?;
+            rule.pattern = Some(regex);
+        }
+
+        if let Some(one_of) = file.one_of {
+            if !is_text {
+                return unsupported("one_of");
+            }
+            rule.one_of = Some(one_of);
+        }
+
+        Ok(rule)
+    }
+
+    fn check_text(&self, kind: FieldKind, value: &str) -> Result<(), Violation> {
+        let matches_pattern = match &self.pattern {
+            Some(pattern) => pattern.is_match(value),
+            None => true,
+        };
+        let is_allowed = match &self.one_of {
+            Some(allowed) => allowed.iter().any(|a| a == value),
+            None => true,
+        };
+
+        if matches_pattern && is_allowed {
+            return Ok(());
+        }
+
+        let value = value.to_owned();
+        Err(match kind {
+            FieldKind::HairColor => Violation::InvalidHairColor(value),
+            FieldKind::EyeColor => Violation::InvalidEyeColor(value),
+            FieldKind::PassportId => Violation::MalformedPassportId(value),
+            _ => Violation::InvalidCountryId(value),
+        })
+    }
+}
+
+/// A schema as it is written in a TOML file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemaFile {
+    #[serde(default)]
+    required: Vec<String>,
+    #[serde(default)]
+    fields: HashMap<String, RuleFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    range: Option<(u32, u32)>,
+    units: Option<Vec<String>>,
+    range_mm: Option<(f64, f64)>,
+    pattern: Option<String>,
+    one_of: Option<Vec<String>>,
+}
+
+#[derive(Debug)]
+pub enum SchemaError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    /// A key in the schema that is not a passport field
+    UnknownField(String),
+    /// A height unit in the schema that is not supported
+    UnknownUnit(String),
+    /// A pattern that could not be compiled
+    InvalidPattern {
+        field: FieldKind,
+        error: regex::Error,
+    },
+    /// A rule given for a field it can't be applied to, like a `range` for a hair color
+    UnsupportedRule {
+        field: FieldKind,
+        rule: &'static str,
+    },
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::Io(e) => write!(f, "failed to read schema: {}", e),
+            SchemaError::Toml(e) => write!(f, "failed to parse schema: {}", e),
+            SchemaError::UnknownField(k) => write!(f, "unknown field '{}'", k),
+            SchemaError::UnknownUnit(u) => write!(f, "unknown height unit '{}'", u),
+            SchemaError::InvalidPattern { field, error } => {
+                write!(f, "invalid pattern for '{}': {}", field, error)
+            }
+            SchemaError::UnsupportedRule { field, rule } => {
+                write!(f, "'{}' can't be used for '{}'", rule, field)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_schema_test() {
+        let schema: PassportSchema = r##"
+            required = ["byr", "hcl"]
+
+            [fields.byr]
+            range = [1900, 1950]
+
+            [fields.hcl]
+            pattern = "#[0-9a-f]{3}"
+            one_of = ["#fff", "#000"]
+        "##
+        .parse()
+        .unwrap();
+
+        let raw = RawPassport::new(vec!["byr:1926".to_string(), "hcl:#fff".to_string()]);
+        assert!(schema.validate(&raw).is_empty());
+
+        let raw = RawPassport::new(vec!["byr:1980".to_string(), "hcl:#abc".to_string()]);
+        assert_eq!(
+            schema.validate(&raw),
+            vec![
+                Violation::YearOutOfRange {
+                    field: FieldKind::BirthYear,
+                    year: 1980,
+                    min: 1900,
+                    max: 1950
+                },
+                Violation::InvalidHairColor("#abc".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn standard_height_range_test() {
+        let schema = PassportSchema::standard();
+        let check = |h: &str| schema.check(&Field::Height(h.parse().unwrap())).is_ok();
+
+        assert!(!check("149cm") && check("150cm") && check("193cm") && !check("194cm"));
+        assert!(!check("58in") && check("59in") && check("76in") && !check("77in"));
+        assert!(!check("1700mm"));
+    }
+
+    #[test]
+    fn invalid_schema_test() {
+        assert!(matches!(
+            "[fields.xyz]\nrange = [1, 2]".parse::<PassportSchema>(),
+            Err(SchemaError::UnknownField(_))
+        ));
+
+        assert!(matches!(
+            "[fields.hcl]\nrange = [1, 2]".parse::<PassportSchema>(),
+            Err(SchemaError::UnsupportedRule {
+                field: FieldKind::HairColor,
+                rule: "range"
+            })
+        ));
+
+        assert!(matches!(
+            "[fields.hgt]\nunits = [\"cm\", \"yd\"]".parse::<PassportSchema>(),
+            Err(SchemaError::UnknownUnit(_))
+        ));
+    }
+}
@@ -1,149 +1,381 @@
-
-use std::{convert::TryFrom, convert::TryInto, str::FromStr};
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct Seating {
-    row: [FrontBack; 7],
-    column: [LeftRight; 3],
-    row_value: u32,
-    column_value: u32,
-    id: u32,
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+
+/// The number of bits a boarding pass uses for the row and the column of a seat
+///
+/// The row is written with `F` (0) and `B` (1), followed by the column written with `L` (0) and `R` (1),
+/// both starting with the most significant bit.
+/// An airplane may have fewer rows than the row bits allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Geometry {
+    row_bits: u32,
+    column_bits: u32,
+    rows: u64,
 }
 
-impl Seating {
-    fn new(row: [FrontBack; 7], column: [LeftRight; 3]) -> Self {
-        let row_value = Seating::evaluate_row(&row);
-        let column_value = Seating::evaluate_column(&column);
-        let id = Seating::evaluate_id(row_value, column_value);
+impl Geometry {
+    /// 128 rows of 8 seats
+    pub const STANDARD: Geometry = Geometry {
+        row_bits: 7,
+        column_bits: 3,
+        rows: 128,
+    };
+
+    /// # Panics
+    /// * if the seat id would not fit in 32 bits
+    pub fn new(row_bits: u32, column_bits: u32) -> Self {
+        assert!(
+            row_bits + column_bits <= 32,
+            "a seat id can have at most 32 bits"
+        );
         Self {
-            row,
-            column,
-            row_value,
-            column_value,
-            id,
+            row_bits,
+            column_bits,
+            rows: 1 << row_bits,
         }
     }
 
-    pub fn row(&self) -> u32 {
-        self.row_value
+    /// Limits the airplane to its first `rows` rows
+    ///
+    /// # Panics
+    /// * if there are more rows than the row bits allow
+    pub fn with_rows(self, rows: u64) -> Self {
+        assert!(
+            rows <= 1 << self.row_bits,
+            "{} row bits allow at most {} rows",
+            self.row_bits,
+            1u64 << self.row_bits
+        );
+        Self { rows, ..self }
     }
 
-    pub fn column(&self) -> u32 {
-        self.column_value
+    pub fn row_bits(&self) -> u32 {
+        self.row_bits
     }
 
-    pub fn id(&self) -> u32 {
-        self.id
+    pub fn column_bits(&self) -> u32 {
+        self.column_bits
     }
 
-    fn evaluate_id(row_value: u32, column_value: u32) -> u32 {
-        8 * row_value + column_value
+    /// The number of characters of a boarding pass
+    pub fn code_len(&self) -> usize {
+        (self.row_bits + self.column_bits) as usize
     }
 
-    fn evaluate_row(fbs: &[FrontBack; 7]) -> u32 {
-        let (mut low, mut high) = (0, 127);
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
 
-        for fb in fbs {
-            let mid = (low + high) / 2;
+    pub fn columns(&self) -> u64 {
+        1 << self.column_bits
+    }
 
-            match fb {
-                FrontBack::Front => {
-                    high = mid;
-                }
-                FrontBack::Back => {
-                    low = mid + 1;
+    /// # Returns
+    /// * the seat at `row` and `column`, or `OutOfRange` if it is not in the airplane
+    pub fn seat(&self, row: u32, column: u32) -> Result<Seating, BoardingPassError> {
+        if u64::from(row) >= self.rows() || u64::from(column) >= self.columns() {
+            return Err(BoardingPassError::OutOfRange { row, column });
+        }
+
+        Ok(Seating {
+            geometry: *self,
+            row,
+            column,
+        })
+    }
+
+    /// Decodes a boarding pass like `FBFBBFFRLR`
+    pub fn decode(&self, code: &str) -> Result<Seating, BoardingPassError> {
+        let found = code.chars().count();
+        if found != self.code_len() {
+            return Err(BoardingPassError::WrongLength {
+                expected: self.code_len(),
+                found,
+            });
+        }
+
+        // the boarding pass is just the seat id written in binary
+        let mut id: u32 = 0;
+        for (position, c) in code.chars().enumerate() {
+            let is_row = position < self.row_bits as usize;
+            let bit = match (c, is_row) {
+                ('F', true) | ('L', false) => 0,
+                ('B', true) | ('R', false) => 1,
+                _ => {
+                    return Err(BoardingPassError::InvalidCharacter {
+                        position,
+                        found: c,
+                        expected: if is_row { ('F', 'B') } else { ('L', 'R') },
+                    })
                 }
-            }
+            };
+            id = (id << 1) | bit;
         }
 
-        debug_assert_eq!(low, high);
+        let column_mask = u32::MAX.checked_shr(32 - self.column_bits).unwrap_or(0);
+        let row = id.checked_shr(self.column_bits).unwrap_or(0);
+        self.seat(row, id & column_mask)
+    }
+}
 
-        low
+impl Default for Geometry {
+    fn default() -> Self {
+        Geometry::STANDARD
     }
+}
 
-    fn evaluate_column(lrs: &[LeftRight; 3]) -> u32 {
-        let (mut low, mut high) = (0, 7);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Seating {
+    geometry: Geometry,
+    row: u32,
+    column: u32,
+}
 
-        for fb in lrs {
-            let mid = (low + high) / 2;
+impl Seating {
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
 
-            match fb {
-                LeftRight::Left => {
-                    high = mid;
-                }
-                LeftRight::Right => {
-                    low = mid + 1;
-                }
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    /// The row and the column as one binary number, which is `8 * row + column` with 8 columns
+    pub fn id(&self) -> u32 {
+        self.row.checked_shl(self.geometry.column_bits).unwrap_or(0) | self.column
+    }
+
+    /// Encodes the seat as a boarding pass, the inverse of `Geometry::decode`
+    pub fn code(&self) -> String {
+        let row =
+            (0..self.geometry.row_bits)
+                .rev()
+                .map(|bit| if self.row >> bit & 1 == 1 { 'B' } else { 'F' });
+        let column = (0..self.geometry.column_bits).rev().map(|bit| {
+            if self.column >> bit & 1 == 1 {
+                'R'
+            } else {
+                'L'
             }
-        }
+        });
+        row.chain(column).collect()
+    }
+}
 
-        debug_assert_eq!(low, high);
+impl Display for Seating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
 
-        low
+impl FromStr for Seating {
+    type Err = BoardingPassError;
+
+    /// Decodes a boarding pass of the standard geometry
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Geometry::STANDARD.decode(s)
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-enum FrontBack {
-    Front,
-    Back,
+pub enum BoardingPassError {
+    WrongLength {
+        expected: usize,
+        found: usize,
+    },
+    /// A character other than the two allowed at `position`, starting at 0
+    InvalidCharacter {
+        position: usize,
+        found: char,
+        expected: (char, char),
+    },
+    /// A seat that is not in the airplane
+    OutOfRange {
+        row: u32,
+        column: u32,
+    },
 }
 
-impl TryFrom<char> for FrontBack {
-    type Error = ();
-
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        Ok(match value {
-            'F' => FrontBack::Front,
-            'B' => FrontBack::Back,
-            _ => return Err(()),
-        })
+impl Display for BoardingPassError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardingPassError::WrongLength { expected, found } => {
+                write!(f, "expected {} characters, found {}", expected, found)
+            }
+            BoardingPassError::InvalidCharacter {
+                position,
+                found,
+                expected: (zero, one),
+            } => write!(
+                f,
+                "expected '{}' or '{}' at position {}, found '{}'",
+                zero, one, position, found
+            ),
+            BoardingPassError::OutOfRange { row, column } => {
+                write!(f, "row {}, column {} is not in the airplane", row, column)
+            }
+        }
     }
 }
 
+/// A bad boarding pass in a manifest, with its 1-indexed line number
 #[derive(Debug, Clone, PartialEq)]
-enum LeftRight {
-    Left,
-    Right,
+pub struct ManifestError {
+    pub line: usize,
+    pub error: BoardingPassError,
 }
 
-impl TryFrom<char> for LeftRight {
-    type Error = ();
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
 
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        Ok(match value {
-            'L' => LeftRight::Left,
-            'R' => LeftRight::Right,
-            _ => return Err(()),
-        })
+/// Decodes a manifest of one boarding pass per line, ignoring blank lines
+///
+/// # Returns
+/// * every seat if all boarding passes are valid, otherwise every bad boarding pass
+pub fn validate_manifest(
+    geometry: Geometry,
+    manifest: &str,
+) -> Result<Vec<Seating>, Vec<ManifestError>> {
+    let mut seatings = Vec::new();
+    let mut errors = Vec::new();
+
+    for (idx, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match geometry.decode(line) {
+            Ok(seating) => seatings.push(seating),
+            Err(error) => errors.push(ManifestError {
+                line: idx + 1,
+                error,
+            }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(seatings)
+    } else {
+        Err(errors)
     }
 }
 
-impl FromStr for Seating {
-    type Err = ();
+pub fn validate_manifest_from_file(
+    geometry: Geometry,
+    file: &str,
+) -> Result<Vec<Seating>, Vec<ManifestError>> {
+    validate_manifest(geometry, &std::fs::read_to_string(file).unwrap())
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 10 {
-            return Err(());
+/// The occupancy of every seat of an airplane, built from the boarding passes of its passengers
+#[derive(Debug, Clone)]
+pub struct SeatMap {
+    geometry: Geometry,
+    /// The ids of the seats that are taken
+    ///
+    /// Only the taken seats are stored, since a geometry with many id bits has far more seats than passengers.
+    occupied: BTreeSet<u32>,
+    duplicates: Vec<Seating>,
+}
+
+impl SeatMap {
+    /// # Panics
+    /// * if a seat doesn't have the same geometry as the map
+    pub fn new<I: IntoIterator<Item = Seating>>(geometry: Geometry, seatings: I) -> Self {
+        let mut occupied = BTreeSet::new();
+        let mut duplicates = Vec::new();
+
+        for seating in seatings {
+            assert_eq!(
+                seating.geometry, geometry,
+                "the seat {} is not in this airplane",
+                seating
+            );
+
+            if !occupied.insert(seating.id()) {
+                duplicates.push(seating);
+            }
         }
 
-        let row = &s[..7];
-        let column = &s[7..];
+        Self {
+            geometry,
+            occupied,
+            duplicates,
+        }
+    }
 
-        let row = row
-            .chars()
-            .map(FrontBack::try_from)
-            .collect::<Result<Vec<FrontBack>, _>>()?;
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
 
-        let column = column
-            .chars()
-            .map(LeftRight::try_from)
-            .collect::<Result<Vec<LeftRight>, _>>()?;
+    pub fn is_occupied(&self, seating: &Seating) -> bool {
+        self.occupied.contains(&seating.id())
+    }
 
-        Ok(Seating::new(
-            row.try_into().map_err(|_| ())?,
-            column.try_into().map_err(|_| ())?,
-        ))
+    /// The seats without a boarding pass, ordered by id
+    pub fn empty_seats(&self) -> Vec<Seating> {
+        (0..self.seat_count())
+            .filter(|&id| !self.occupied.contains(&(id as u32)))
+            .map(|id| self.seat_with_id(id))
+            .collect()
+    }
+
+    /// The empty seats whose ids are right between two occupied seats, ordered by id
+    pub fn empty_seats_between_occupied(&self) -> Vec<Seating> {
+        self.occupied
+            .iter()
+            .zip(self.occupied.iter().skip(1))
+            .filter(|(&before, &after)| u64::from(after) - u64::from(before) == 2)
+            .map(|(&before, _)| self.seat_with_id(u64::from(before) + 1))
+            .collect()
+    }
+
+    /// The boarding passes for seats that were already taken, in the order they were given
+    pub fn duplicates(&self) -> &[Seating] {
+        &self.duplicates
+    }
+
+    fn seat_count(&self) -> u64 {
+        self.geometry.rows() * self.geometry.columns()
+    }
+
+    fn seat_with_id(&self, id: u64) -> Seating {
+        let columns = self.geometry.columns();
+        Seating {
+            geometry: self.geometry,
+            row: (id / columns) as u32,
+            column: (id % columns) as u32,
+        }
+    }
+}
+
+impl Display for SeatMap {
+    /// Draws one line per row with `#` for occupied and `.` for empty seats, and an aisle in the middle
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let columns = self.geometry.columns();
+        let aisle = columns / 2;
+        let width = self.geometry.rows().saturating_sub(1).to_string().len();
+
+        for row in 0..self.geometry.rows() {
+            write!(f, "{:>width$} ", row, width = width)?;
+            for column in 0..columns {
+                if column == aisle && aisle > 0 {
+                    write!(f, " ")?;
+                }
+                let occupied = self.occupied.contains(&((row * columns + column) as u32));
+                write!(f, "{}", if occupied { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -165,5 +397,118 @@ mod tests {
         assert_eq!(seating.row(), row);
         assert_eq!(seating.column(), column);
         assert_eq!(seating.id(), id);
+        assert_eq!(seating.code(), input);
+    }
+
+    #[test]
+    fn custom_geometry_test() {
+        let geometry = Geometry::new(9, 4);
+        let seating = geometry.seat(300, 10).unwrap();
+
+        assert_eq!(seating.id(), 300 * 16 + 10);
+        assert_eq!(seating.code(), "BFFBFBBFFRLRL");
+        assert_eq!(geometry.decode("BFFBFBBFFRLRL"), Ok(seating));
+    }
+
+    #[test]
+    fn boarding_pass_error_test() {
+        let geometry = Geometry::new(9, 4).with_rows(300);
+
+        assert_eq!(
+            geometry.decode("FBFBBFFRLR"),
+            Err(BoardingPassError::WrongLength {
+                expected: 13,
+                found: 10
+            })
+        );
+        assert_eq!(
+            geometry.decode("FBFBBFFRLRRLF"),
+            Err(BoardingPassError::InvalidCharacter {
+                position: 7,
+                found: 'R',
+                expected: ('F', 'B')
+            })
+        );
+        assert_eq!(
+            geometry.decode("BFFBFBBFFRLRL"),
+            Err(BoardingPassError::OutOfRange {
+                row: 300,
+                column: 10
+            })
+        );
+        assert!(geometry.seat(299, 15).is_ok());
+    }
+
+    #[test]
+    fn validate_manifest_test() {
+        let manifest = "FBFBBFFRLR\nBFFFBBFRRR\n\nFBFBBFFRLX\nFBF\n";
+
+        assert_eq!(
+            validate_manifest(Geometry::STANDARD, manifest),
+            Err(vec![
+                ManifestError {
+                    line: 4,
+                    error: BoardingPassError::InvalidCharacter {
+                        position: 9,
+                        found: 'X',
+                        expected: ('L', 'R')
+                    }
+                },
+                ManifestError {
+                    line: 5,
+                    error: BoardingPassError::WrongLength {
+                        expected: 10,
+                        found: 3
+                    }
+                }
+            ])
+        );
+
+        let seatings = validate_manifest(Geometry::STANDARD, &manifest[..22]).unwrap();
+        assert_eq!(
+            seatings.iter().map(|s| s.id()).collect::<Vec<_>>(),
+            vec![357, 567]
+        );
+    }
+
+    #[test]
+    fn seat_map_test() {
+        let geometry = Geometry::new(2, 2);
+        let passes = vec!["FFLL", "FFLR", "FFRR", "FBLL", "BFLR", "BFLR", "BBLL"];
+        let map = SeatMap::new(
+            geometry,
+            passes.into_iter().map(|p| geometry.decode(p).unwrap()),
+        );
+
+        let codes = |seats: Vec<Seating>| seats.iter().map(|s| s.code()).collect::<Vec<_>>();
+        assert_eq!(
+            codes(map.empty_seats()),
+            vec!["FFRL", "FBLR", "FBRL", "FBRR", "BFLL", "BFRL", "BFRR", "BBLR", "BBRL", "BBRR"]
+        );
+        assert_eq!(codes(map.empty_seats_between_occupied()), vec!["FFRL"]);
+        assert_eq!(codes(map.duplicates().to_vec()), vec!["BFLR"]);
+        assert_eq!(map.to_string(), "0 ## .#\n1 #. ..\n2 .# ..\n3 #. ..\n");
+
+        let empty = SeatMap::new(geometry.with_rows(0), Vec::new());
+        assert!(empty.empty_seats().is_empty());
+        assert_eq!(empty.to_string(), "");
+    }
+
+    #[test]
+    fn large_seat_map_test() {
+        let geometry = Geometry::new(20, 12);
+        let seats = vec![
+            geometry.seat(1_000_000, 4094).unwrap(),
+            geometry.seat(1_000_000, 4095).unwrap(),
+            geometry.seat(1_000_001, 1).unwrap(),
+        ];
+        let map = SeatMap::new(geometry, seats.clone());
+
+        assert!(seats.iter().all(|s| map.is_occupied(s)));
+        assert!(!map.is_occupied(&geometry.seat(1_000_001, 0).unwrap()));
+        assert_eq!(
+            map.empty_seats_between_occupied(),
+            vec![geometry.seat(1_000_001, 0).unwrap()]
+        );
     }
 }
@@ -0,0 +1,310 @@
+pub mod rule;
+pub mod simulation;
+
+use std::{cmp::Ordering, convert::TryFrom, fmt::Display, str::FromStr};
+
+use simple_grid::{Grid, GridIndex};
+
+use rule::SeatingRule;
+use simulation::{Generations, Outcome, Simulation};
+
+const STEPS: [(isize, isize); 8] = [
+    (0, 1),   // up
+    (1, 1),   // up-right
+    (1, 0),   // right
+    (1, -1),  // down-right
+    (0, -1),  // down
+    (-1, -1), // down-left
+    (-1, 0),  // left
+    (-1, 1),  // up-left
+];
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct FerrySeating {
+    grid: Grid<Seat>,
+}
+
+impl FerrySeating {
+    fn new(grid: Grid<Seat>) -> Self {
+        Self { grid }
+    }
+
+    pub fn count_occupied_seats(&self) -> usize {
+        self.grid
+            .cell_iter()
+            .filter(|c| matches!(c, Seat::Occupied))
+            .count()
+    }
+
+    pub fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.grid.height()
+    }
+
+    /// # Returns
+    /// * whether there is a seat at `idx`, rather than floor
+    pub fn is_seat(&self, idx: GridIndex) -> bool {
+        matches!(self.seat_at(idx), Some(Seat::Empty) | Some(Seat::Occupied))
+    }
+
+    /// The seats directly next to `idx`, including diagonally
+    pub fn adjacent_seats(&self, idx: GridIndex) -> Vec<GridIndex> {
+        STEPS
+            .iter()
+            .filter_map(|&(col_offset, row_offset)| self.add_to_idx(idx, (col_offset, row_offset)))
+            .filter(|&idx| self.is_seat(idx))
+            .collect()
+    }
+
+    fn step_until_seat(
+        &self,
+        mut start: GridIndex,
+        (col_step, row_step): (isize, isize),
+    ) -> Option<GridIndex> {
+        loop {
+            let to_idx = self.add_to_idx(start, (col_step, row_step))?;
+            if self.is_seat(to_idx) {
+                return Some(to_idx);
+            }
+            start = to_idx;
+        }
+    }
+
+    /// The first seat in each of the eight directions from `idx`, looking over the floor
+    pub fn visible_seats(&self, idx: GridIndex) -> Vec<GridIndex> {
+        STEPS
+            .iter()
+            .filter_map(|&step| self.step_until_seat(idx, step))
+            .collect()
+    }
+
+    fn seat_at(&self, idx: GridIndex) -> Option<&Seat> {
+        self.grid.get(idx)
+    }
+
+    /// Applies `rule` to every seat at once
+    pub fn step_once(&self, rule: &SeatingRule) -> FerrySeating {
+        let mut next = self.grid.clone();
+
+        for row in 0..self.grid.height() {
+            for col in 0..self.grid.width() {
+                let idx = GridIndex::new(row, col);
+                let seat = self.seat_at(idx).unwrap();
+                if matches!(seat, Seat::Floor) {
+                    continue;
+                }
+
+                let occupied_neighbors = rule
+                    .neighbors(self, idx)
+                    .into_iter()
+                    .filter(|&n| matches!(self.grid[n], Seat::Occupied))
+                    .count();
+                next[idx] = rule.next_seat(seat, occupied_neighbors);
+            }
+        }
+
+        FerrySeating::new(next)
+    }
+
+    /// Applies `rule` until no seat changes anymore
+    ///
+    /// # Returns
+    /// * the stable seating, or how the simulation ended if it never becomes stable
+    pub fn step_until_stable(&self, rule: &SeatingRule) -> Result<FerrySeating, Outcome> {
+        let mut simulation = Simulation::new(self, rule);
+        match simulation.run(usize::MAX) {
+            Outcome::Stable { .. } => Ok(simulation.seating()),
+            outcome => Err(outcome),
+        }
+    }
+
+    /// # Panics
+    /// * if the seating never becomes stable
+    pub fn step_until_stable_neighbor(&self) -> FerrySeating {
+        self.step_until_stable(&SeatingRule::ADJACENT)
+            .expect("the seating never becomes stable")
+    }
+
+    /// # Panics
+    /// * if the seating never becomes stable
+    pub fn step_until_stable_vision(&self) -> FerrySeating {
+        self.step_until_stable(&SeatingRule::LINE_OF_SIGHT)
+            .expect("the seating never becomes stable")
+    }
+
+    /// Iterates over the generations of `rule` applied to this seating, starting with this seating
+    pub fn generations(&self, rule: &SeatingRule) -> Generations {
+        Generations::new(Simulation::new(self, rule))
+    }
+
+    fn add_to_idx(
+        &self,
+        idx: GridIndex,
+        (col_offset, row_offset): (isize, isize),
+    ) -> Option<GridIndex> {
+        let eval_col = match col_offset.cmp(&0) {
+            Ordering::Less => {
+                if idx.column() > 0 {
+                    Some(idx.column() - col_offset.abs() as usize)
+                } else {
+                    None
+                }
+            }
+            Ordering::Equal => Some(idx.column()),
+            Ordering::Greater => {
+                if idx.column() < self.grid.width() - 1 {
+                    Some(idx.column() + col_offset as usize)
+                } else {
+                    None
+                }
+            }
+        };
+        let eval_row = match row_offset.cmp(&0) {
+            Ordering::Less => {
+                if idx.row() > 0 {
+                    Some(idx.row() - row_offset.abs() as usize)
+                } else {
+                    None
+                }
+            }
+            Ordering::Equal => Some(idx.row()),
+            Ordering::Greater => {
+                if idx.row() < self.grid.height() - 1 {
+                    Some(idx.row() + row_offset as usize)
+                } else {
+                    None
+                }
+            }
+        };
+
+        if let (Some(col), Some(row)) = (eval_col, eval_row) {
+            Some(GridIndex::new(row, col))
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for FerrySeating {
+    /// Draws the seating in the same format it is parsed from
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.grid.height() {
+            for seat in self.grid.row_iter(row) {
+                write!(f, "{}", seat)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for FerrySeating {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<_> = s
+            .split('\n')
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        let height = lines.len();
+        let width = lines.first().ok_or(())?.len();
+
+        let mut grid_data = Vec::new();
+        for line in lines {
+            if line.len() != width {
+                return Err(());
+            }
+            let mut seats = line
+                .chars()
+                .map(Seat::try_from)
+                .collect::<Result<Vec<Seat>, _>>()?;
+            grid_data.append(&mut seats);
+        }
+
+        let grid = Grid::new(width, height, grid_data);
+
+        Ok(FerrySeating::new(grid))
+    }
+}
+
+pub fn parse_ferry_seating_from_input_file(file: &str) -> FerrySeating {
+    std::fs::read_to_string(file).unwrap().parse().unwrap()
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Seat {
+    Empty,
+    Occupied,
+    Floor,
+}
+
+impl Display for Seat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            Seat::Empty => "L",
+            Seat::Occupied => "#",
+            Seat::Floor => ".",
+        };
+
+        write!(f, "{}", output)
+    }
+}
+
+impl TryFrom<char> for Seat {
+    type Error = ();
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
+            'L' => Seat::Empty,
+            '.' => Seat::Floor,
+            '#' => Seat::Occupied,
+            _ => return Err(()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) const EXAMPLE: &str = "
+        L.LL.LL.LL
+        LLLLLLL.LL
+        L.L.L..L..
+        LLLL.LL.LL
+        L.LL.LL.LL
+        L.LLLLL.LL
+        ..L.L.....
+        LLLLLLLLLL
+        L.LLLLLL.L
+        L.LLLLL.LL
+    ";
+
+    #[test]
+    fn parse_ferry_seating_from_input_file_test() {
+        let ferry = parse_ferry_seating_from_input_file("inputs/day11_example.txt");
+
+        assert_eq!(ferry.grid.width(), 10);
+        assert_eq!(ferry.grid.height(), 10);
+    }
+
+    #[test]
+    fn display_and_generations_test() {
+        let seating: FerrySeating = EXAMPLE.parse().unwrap();
+        let rendered = seating.to_string();
+        assert!(rendered.starts_with("L.LL.LL.LL\nLLLLLLL.LL\n"));
+        assert!(rendered.parse::<FerrySeating>().unwrap() == seating);
+
+        let generations: Vec<_> = seating.generations(&SeatingRule::ADJACENT).collect();
+        assert_eq!(generations.len(), 6);
+        assert!(generations[0] == seating);
+        assert!(generations[1].to_string().starts_with("#.##.##.##\n"));
+        assert_eq!(generations[5].count_occupied_seats(), 37);
+    }
+}
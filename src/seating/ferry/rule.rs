@@ -0,0 +1,95 @@
+use simple_grid::GridIndex;
+
+use super::{FerrySeating, Seat};
+
+/// Finds the seats that affect the seat at an index, which depends only on the layout and never on who sits where
+pub type Neighborhood = fn(&FerrySeating, GridIndex) -> Vec<GridIndex>;
+
+/// How people choose their seats: an empty seat gets taken if at most `birth` of its neighbors are occupied,
+/// and an occupied seat is left if at least `death` of its neighbors are occupied
+#[derive(Debug, Clone, Copy)]
+pub struct SeatingRule {
+    neighborhood: Neighborhood,
+    birth: usize,
+    death: usize,
+}
+
+impl SeatingRule {
+    /// People only look at the seats right next to them, and leave if 4 of them are occupied
+    pub const ADJACENT: SeatingRule = SeatingRule {
+        neighborhood: FerrySeating::adjacent_seats,
+        birth: 0,
+        death: 4,
+    };
+
+    /// People look at the first seat in each direction, and leave if 5 of them are occupied
+    pub const LINE_OF_SIGHT: SeatingRule = SeatingRule {
+        neighborhood: FerrySeating::visible_seats,
+        birth: 0,
+        death: 5,
+    };
+
+    pub fn new(neighborhood: Neighborhood, birth: usize, death: usize) -> Self {
+        Self {
+            neighborhood,
+            birth,
+            death,
+        }
+    }
+
+    pub fn birth(&self) -> usize {
+        self.birth
+    }
+
+    pub fn death(&self) -> usize {
+        self.death
+    }
+
+    pub fn neighbors(&self, seating: &FerrySeating, idx: GridIndex) -> Vec<GridIndex> {
+        (self.neighborhood)(seating, idx)
+    }
+
+    pub(super) fn next_seat(&self, seat: &Seat, occupied_neighbors: usize) -> Seat {
+        match seat {
+            Seat::Empty if occupied_neighbors <= self.birth => Seat::Occupied,
+            Seat::Occupied if occupied_neighbors >= self.death => Seat::Empty,
+            other => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seating::ferry::tests::EXAMPLE;
+
+    #[test]
+    fn preset_rules_test() {
+        let seating: FerrySeating = EXAMPLE.parse().unwrap();
+
+        let stable = seating.step_until_stable_neighbor();
+        assert_eq!(stable.count_occupied_seats(), 37);
+
+        let stable = seating.step_until_stable_vision();
+        assert_eq!(stable.count_occupied_seats(), 26);
+    }
+
+    #[test]
+    fn custom_rule_test() {
+        // people only care about the seats beside them in the same row, and leave as soon as one is taken
+        let row_neighbors: Neighborhood = |seating, idx| {
+            seating
+                .adjacent_seats(idx)
+                .into_iter()
+                .filter(|n| n.row() == idx.row())
+                .collect()
+        };
+        let rule = SeatingRule::new(row_neighbors, 0, 1);
+
+        let seating: FerrySeating = "LLL\nL.L".parse().unwrap();
+        let next = seating.step_once(&rule);
+        assert_eq!(next.count_occupied_seats(), 5);
+        let next = next.step_once(&rule);
+        assert_eq!(next.count_occupied_seats(), 2);
+    }
+}
@@ -0,0 +1,334 @@
+use std::{collections::HashMap, mem};
+
+use bitvec::prelude::*;
+use simple_grid::{Grid, GridIndex};
+
+use super::{rule::SeatingRule, FerrySeating, Seat};
+
+/// Runs a seating rule on a layout, generation by generation
+///
+/// The neighbors of every seat are found once when the simulation is created, and the floor is left out entirely.
+/// Each generation is written into a second buffer which is then swapped with the current one,
+/// and only the seats that changed or have a neighbor that changed are evaluated again.
+/// With the `parallel` feature, the seats of a generation are evaluated in parallel, with the same results.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    /// The layout the simulation started from, to put the seats back into a grid
+    layout: Grid<Seat>,
+    /// The position in the grid of each seat
+    positions: Vec<GridIndex>,
+    /// The neighbors of seat `i` are `neighbors[neighbor_offsets[i]..neighbor_offsets[i + 1]]`
+    neighbor_offsets: Vec<usize>,
+    neighbors: Vec<u32>,
+    /// The seats that have seat `i` as a neighbor, stored like `neighbors`
+    watcher_offsets: Vec<usize>,
+    watchers: Vec<u32>,
+    birth: usize,
+    death: usize,
+    current: Vec<bool>,
+    next: Vec<bool>,
+    /// The seats to evaluate in the next generation
+    pending: Vec<u32>,
+    is_pending: Vec<bool>,
+    generation: usize,
+}
+
+impl Simulation {
+    pub fn new(seating: &FerrySeating, rule: &SeatingRule) -> Self {
+        let width = seating.width();
+
+        let mut seat_numbers = vec![None; width * seating.height()];
+        let mut positions = Vec::new();
+        let mut current = Vec::new();
+        for row in 0..seating.height() {
+            for col in 0..width {
+                let idx = GridIndex::new(row, col);
+                match seating.grid[idx] {
+                    Seat::Floor => {}
+                    ref seat => {
+                        seat_numbers[row * width + col] = Some(positions.len() as u32);
+                        positions.push(idx);
+                        current.push(matches!(seat, Seat::Occupied));
+                    }
+                }
+            }
+        }
+
+        let mut neighbor_offsets = Vec::with_capacity(positions.len() + 1);
+        let mut neighbors = Vec::new();
+        let mut watcher_counts = vec![0; positions.len()];
+        neighbor_offsets.push(0);
+        for &idx in &positions {
+            // a neighborhood may include floor, which is never occupied and can be ignored
+            for neighbor in rule.neighbors(seating, idx) {
+                if let Some(number) = seat_numbers[neighbor.row() * width + neighbor.column()] {
+                    neighbors.push(number);
+                    watcher_counts[number as usize] += 1;
+                }
+            }
+            neighbor_offsets.push(neighbors.len());
+        }
+
+        let mut watcher_offsets = Vec::with_capacity(positions.len() + 1);
+        watcher_offsets.push(0);
+        for count in &watcher_counts {
+            watcher_offsets.push(watcher_offsets.last().unwrap() + count);
+        }
+        let mut watchers = vec![0; neighbors.len()];
+        let mut filled = watcher_offsets.clone();
+        for seat in 0..positions.len() {
+            for &neighbor in &neighbors[neighbor_offsets[seat]..neighbor_offsets[seat + 1]] {
+                watchers[filled[neighbor as usize]] = seat as u32;
+                filled[neighbor as usize] += 1;
+            }
+        }
+
+        let seat_count = positions.len();
+        Self {
+            layout: seating.grid.clone(),
+            positions,
+            neighbor_offsets,
+            neighbors,
+            watcher_offsets,
+            watchers,
+            birth: rule.birth(),
+            death: rule.death(),
+            next: current.clone(),
+            current,
+            pending: (0..seat_count as u32).collect(),
+            is_pending: vec![true; seat_count],
+            generation: 0,
+        }
+    }
+
+    /// The number of generations computed so far
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn count_occupied_seats(&self) -> usize {
+        self.current.iter().filter(|&&occupied| occupied).count()
+    }
+
+    /// # Returns
+    /// * the seating of the current generation
+    pub fn seating(&self) -> FerrySeating {
+        let mut grid = self.layout.clone();
+        for (&idx, &occupied) in self.positions.iter().zip(&self.current) {
+            grid[idx] = if occupied {
+                Seat::Occupied
+            } else {
+                Seat::Empty
+            };
+        }
+        FerrySeating::new(grid)
+    }
+
+    /// Computes the next generation
+    ///
+    /// # Returns
+    /// * the number of seats that changed
+    pub fn step(&mut self) -> usize {
+        #[cfg(feature = "parallel")]
+        let next_states: Vec<bool> = {
+            use rayon::prelude::*;
+            self.pending
+                .par_iter()
+                .map(|&seat| self.next_state(seat as usize))
+                .collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let next_states: Vec<bool> = self
+            .pending
+            .iter()
+            .map(|&seat| self.next_state(seat as usize))
+            .collect();
+
+        // the states are written in the order of `pending` either way, so both paths give the same result
+        let mut changed = Vec::new();
+        for (&seat, &next) in self.pending.iter().zip(&next_states) {
+            let seat = seat as usize;
+            self.next[seat] = next;
+            if next != self.current[seat] {
+                changed.push(seat);
+            }
+        }
+
+        // seats that were not evaluated didn't change in the previous generation either,
+        // so they already have the same state in both buffers
+        mem::swap(&mut self.current, &mut self.next);
+        self.generation += 1;
+
+        for &seat in &self.pending {
+            self.is_pending[seat as usize] = false;
+        }
+        self.pending.clear();
+        for &seat in &changed {
+            let watchers =
+                &self.watchers[self.watcher_offsets[seat]..self.watcher_offsets[seat + 1]];
+            for &s in std::iter::once(&(seat as u32)).chain(watchers) {
+                if !self.is_pending[s as usize] {
+                    self.is_pending[s as usize] = true;
+                    self.pending.push(s);
+                }
+            }
+        }
+
+        changed.len()
+    }
+
+    /// # Returns
+    /// * whether `seat` is occupied in the next generation
+    fn next_state(&self, seat: usize) -> bool {
+        let occupied_neighbors = self.neighbors
+            [self.neighbor_offsets[seat]..self.neighbor_offsets[seat + 1]]
+            .iter()
+            .filter(|&&n| self.current[n as usize])
+            .count();
+
+        if self.current[seat] {
+            occupied_neighbors < self.death
+        } else {
+            occupied_neighbors <= self.birth
+        }
+    }
+
+    /// Steps until a generation repeats an earlier one, or `max_generations` have been computed
+    ///
+    /// Every generation is remembered with one bit per seat to find the repetition.
+    /// The simulation stops at the first generation that was seen before.
+    pub fn run(&mut self, max_generations: usize) -> Outcome {
+        let mut seen = HashMap::new();
+        seen.insert(self.state(), self.generation);
+
+        while self.generation < max_generations {
+            let changed = self.step();
+
+            // a generation without changes repeats the previous one, no need to look it up
+            let first_repeated = if changed == 0 {
+                Some(self.generation - 1)
+            } else {
+                seen.get(&self.state()).copied()
+            };
+
+            match first_repeated {
+                Some(generation) if generation + 1 == self.generation => {
+                    return Outcome::Stable { generation }
+                }
+                Some(generation) => {
+                    return Outcome::Cycle {
+                        first_repeated: generation,
+                        period: self.generation - generation,
+                    }
+                }
+                None => {
+                    seen.insert(self.state(), self.generation);
+                }
+            }
+        }
+
+        Outcome::LimitReached
+    }
+
+    fn state(&self) -> BitVec {
+        self.current.iter().copied().collect()
+    }
+}
+
+/// How a simulation ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The seats don't change anymore from `generation` on
+    Stable { generation: usize },
+    /// Generation `first_repeated` comes back every `period` generations, forever
+    Cycle {
+        first_repeated: usize,
+        period: usize,
+    },
+    /// Neither happened within the maximum number of generations
+    LimitReached,
+}
+
+/// An iterator over the generations of a simulation, starting with the initial seating
+///
+/// It ends with the first stable generation, and never ends if the seating cycles.
+#[derive(Debug, Clone)]
+pub struct Generations {
+    simulation: Simulation,
+    done: bool,
+}
+
+impl Generations {
+    pub fn new(simulation: Simulation) -> Self {
+        Self {
+            simulation,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for Generations {
+    type Item = FerrySeating;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let seating = self.simulation.seating();
+        // the next generation is computed ahead to know if this one is the last
+        self.done = self.simulation.step() == 0;
+        Some(seating)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seating::ferry::{rule::Neighborhood, tests::EXAMPLE};
+
+    #[test]
+    fn simulation_matches_grid_steps_test() {
+        let seating: FerrySeating = EXAMPLE.parse().unwrap();
+
+        for rule in &[SeatingRule::ADJACENT, SeatingRule::LINE_OF_SIGHT] {
+            let mut simulation = Simulation::new(&seating, rule);
+            let mut expected = seating.clone();
+
+            for _ in 0..8 {
+                simulation.step();
+                expected = expected.step_once(rule);
+                assert!(simulation.seating() == expected);
+                assert_eq!(
+                    simulation.count_occupied_seats(),
+                    expected.count_occupied_seats()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn run_outcome_test() {
+        let seating: FerrySeating = EXAMPLE.parse().unwrap();
+
+        let mut simulation = Simulation::new(&seating, &SeatingRule::ADJACENT);
+        assert_eq!(simulation.run(3), Outcome::LimitReached);
+        assert_eq!(simulation.run(100), Outcome::Stable { generation: 5 });
+        assert_eq!(simulation.count_occupied_seats(), 37);
+
+        // with two seats watching each other, both keep standing up and sitting down together
+        let other_seat: Neighborhood = |_seating, idx| {
+            let column = 1 - idx.column();
+            vec![GridIndex::new(idx.row(), column)]
+        };
+        let rule = SeatingRule::new(other_seat, 0, 1);
+        let mut simulation = Simulation::new(&"LL".parse().unwrap(), &rule);
+        assert_eq!(
+            simulation.run(100),
+            Outcome::Cycle {
+                first_repeated: 0,
+                period: 2
+            }
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::{collections::VecDeque, fmt::Display};
+
+/// The ways the columns of the tickets can be assigned to fields, so that every column gets a different field
+#[derive(Debug, Clone, PartialEq)]
+pub enum Assignment {
+    /// The only assignment, giving the field index for each column
+    Unique(Vec<usize>),
+    /// More than one assignment is possible
+    Ambiguous(Vec<Vec<usize>>),
+    /// No assignment is possible
+    Impossible(Conflict),
+}
+
+/// Columns that can't all get a different field, because together they only fit fewer fields
+///
+/// This is a violation of Hall's condition, but not necessarily the smallest one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    columns: Vec<usize>,
+    fields: Vec<usize>,
+}
+
+impl Conflict {
+    pub fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// The only fields the columns fit, which are fewer than the columns
+    pub fn fields(&self) -> &[usize] {
+        &self.fields
+    }
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "columns {:?} only fit the {} fields {:?}",
+            self.columns,
+            self.fields.len(),
+            self.fields
+        )
+    }
+}
+
+/// Assigns a different field to every column, where `candidates[column]` are the fields the column fits
+///
+/// The assignment is found as a maximum bipartite matching with Hopcroft–Karp.
+/// A `limit` of 0 is treated as 1, so an ambiguous assignment always lists at least one of them.
+/// # Returns
+/// * `Ambiguous` with at most `limit` of the possible assignments, if there is more than one
+/// * `Impossible` with a group of columns that fit fewer fields than there are columns, if there is none
+pub fn assign(candidates: &[Vec<usize>], field_count: usize, limit: usize) -> Assignment {
+    let matching = Matching::maximum(candidates, field_count);
+
+    if let Some(conflict) = matching.conflict(candidates) {
+        return Assignment::Impossible(conflict);
+    }
+
+    let mut assignments = Vec::new();
+    let mut fixed = Vec::with_capacity(candidates.len());
+    enumerate(
+        candidates,
+        field_count,
+        &mut fixed,
+        &mut assignments,
+        limit.max(2),
+    );
+
+    if assignments.len() == 1 {
+        Assignment::Unique(assignments.swap_remove(0))
+    } else {
+        assignments.truncate(limit.max(1));
+        Assignment::Ambiguous(assignments)
+    }
+}
+
+/// Collects every assignment starting with the `fixed` fields of the first columns, until there are `limit` of them
+///
+/// A column is only fixed to a field if the remaining columns can still be matched,
+/// so every branch of the search ends with an assignment.
+fn enumerate(
+    candidates: &[Vec<usize>],
+    field_count: usize,
+    fixed: &mut Vec<usize>,
+    assignments: &mut Vec<Vec<usize>>,
+    limit: usize,
+) {
+    let column = fixed.len();
+    if column == candidates.len() {
+        assignments.push(fixed.clone());
+        return;
+    }
+
+    for &field in &candidates[column] {
+        if assignments.len() >= limit {
+            return;
+        }
+        if fixed.contains(&field) {
+            continue;
+        }
+
+        fixed.push(field);
+        let restricted: Vec<Vec<usize>> = candidates
+            .iter()
+            .enumerate()
+            .map(|(c, fields)| match fixed.get(c) {
+                Some(&f) => vec![f],
+                None => fields
+                    .iter()
+                    .copied()
+                    .filter(|f| !fixed.contains(f))
+                    .collect(),
+            })
+            .collect();
+
+        if Matching::maximum(&restricted, field_count).is_perfect() {
+            enumerate(candidates, field_count, fixed, assignments, limit);
+        }
+        fixed.pop();
+    }
+}
+
+/// A matching of columns to fields
+struct Matching {
+    field_of_column: Vec<Option<usize>>,
+    column_of_field: Vec<Option<usize>>,
+}
+
+impl Matching {
+    /// Finds a maximum matching with Hopcroft–Karp: each phase finds the shortest augmenting paths
+    /// with a breadth-first search, then augments along a maximal set of them with depth-first searches
+    fn maximum(candidates: &[Vec<usize>], field_count: usize) -> Self {
+        let mut matching = Self {
+            field_of_column: vec![None; candidates.len()],
+            column_of_field: vec![None; field_count],
+        };
+
+        let mut layers = vec![None; candidates.len()];
+        while matching.layer(candidates, &mut layers) {
+            for column in 0..candidates.len() {
+                if matching.field_of_column[column].is_none() {
+                    matching.augment(candidates, &mut layers, column);
+                }
+            }
+        }
+
+        matching
+    }
+
+    /// Assigns each column its distance from an unmatched column along alternating paths
+    ///
+    /// # Returns
+    /// * whether an augmenting path exists
+    fn layer(&self, candidates: &[Vec<usize>], layers: &mut [Option<usize>]) -> bool {
+        let mut queue = VecDeque::new();
+        for (column, layer) in layers.iter_mut().enumerate() {
+            if self.field_of_column[column].is_none() {
+                *layer = Some(0);
+                queue.push_back(column);
+            } else {
+                *layer = None;
+            }
+        }
+
+        let mut found = false;
+        while let Some(column) = queue.pop_front() {
+            let next = layers[column].map(|l| l + 1);
+            for &field in &candidates[column] {
+                match self.column_of_field[field] {
+                    None => found = true,
+                    Some(other) if layers[other].is_none() => {
+                        layers[other] = next;
+                        queue.push_back(other);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Follows the layers from `column` to an unmatched field, and flips the path if one is found
+    fn augment(
+        &mut self,
+        candidates: &[Vec<usize>],
+        layers: &mut [Option<usize>],
+        column: usize,
+    ) -> bool {
+        for &field in &candidates[column] {
+            let reached = match self.column_of_field[field] {
+                None => true,
+                Some(other) => {
+                    layers[other] == layers[column].map(|l| l + 1)
+                        && self.augment(candidates, layers, other)
+                }
+            };
+
+            if reached {
+                self.field_of_column[column] = Some(field);
+                self.column_of_field[field] = Some(column);
+                return true;
+            }
+        }
+
+        // no augmenting path goes through this column in this phase
+        layers[column] = None;
+        false
+    }
+
+    fn is_perfect(&self) -> bool {
+        self.field_of_column.iter().all(|f| f.is_some())
+    }
+
+    /// Finds the columns reachable along alternating paths from an unmatched column
+    ///
+    /// In a maximum matching, these columns fit fewer fields than there are of them (Hall's theorem).
+    fn conflict(&self, candidates: &[Vec<usize>]) -> Option<Conflict> {
+        let unmatched = self.field_of_column.iter().position(|f| f.is_none())?;
+
+        let mut seen_columns = vec![false; candidates.len()];
+        let mut seen_fields = vec![false; self.column_of_field.len()];
+        seen_columns[unmatched] = true;
+
+        let mut queue = VecDeque::from(vec![unmatched]);
+        while let Some(column) = queue.pop_front() {
+            for &field in &candidates[column] {
+                if seen_fields[field] {
+                    continue;
+                }
+                seen_fields[field] = true;
+
+                // every field reachable from an unmatched column is matched, otherwise the matching wasn't maximum
+                if let Some(other) = self.column_of_field[field] {
+                    if !seen_columns[other] {
+                        seen_columns[other] = true;
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+
+        let indices = |seen: Vec<bool>| (0..seen.len()).filter(|&i| seen[i]).collect();
+        Some(Conflict {
+            columns: indices(seen_columns),
+            fields: indices(seen_fields),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_assignment_test() {
+        // the example of day 16: row, class, seat
+        let candidates = vec![vec![0], vec![0, 1], vec![0, 1, 2]];
+        assert_eq!(
+            assign(&candidates, 3, 10),
+            Assignment::Unique(vec![0, 1, 2])
+        );
+
+        // the last two columns are forced, but the first two can be swapped
+        let candidates = vec![vec![0, 1], vec![0, 1, 2], vec![2, 3], vec![3]];
+        assert_eq!(
+            assign(&candidates, 4, 10),
+            Assignment::Ambiguous(vec![vec![0, 1, 2, 3], vec![1, 0, 2, 3]])
+        );
+    }
+
+    #[test]
+    fn ambiguous_assignment_test() {
+        let candidates = vec![vec![0, 1, 2]; 3];
+        match assign(&candidates, 3, 4) {
+            Assignment::Ambiguous(assignments) => {
+                assert_eq!(assignments.len(), 4);
+                assert_eq!(assignments[0], vec![0, 1, 2]);
+                assert_eq!(assignments[1], vec![0, 2, 1]);
+            }
+            other => panic!("expected an ambiguous assignment, got {:?}", other),
+        }
+
+        assert_eq!(
+            assign(&candidates, 3, 0),
+            Assignment::Ambiguous(vec![vec![0, 1, 2]])
+        );
+    }
+
+    #[test]
+    fn impossible_assignment_test() {
+        let candidates = vec![vec![0, 1, 2], vec![1], vec![1, 3], vec![3]];
+        match assign(&candidates, 4, 10) {
+            Assignment::Impossible(conflict) => {
+                assert_eq!(conflict.columns(), &[1, 2, 3]);
+                assert_eq!(conflict.fields(), &[1, 3]);
+            }
+            other => panic!("expected no assignment, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::{collections::HashMap, fmt::Display, io::Write};
+
+use serde::de::{value::MapDeserializer, DeserializeOwned};
+
+use super::{Ticket, TicketLengthError, TicketValidator};
+
+/// The name of the field in each column of the tickets, once the fields have been assigned
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMapping {
+    names: Vec<String>,
+}
+
+impl FieldMapping {
+    /// Creates the mapping from an assignment of `validator`'s fields, as returned by `assign_fields`
+    ///
+    /// # Panics
+    /// * if an index is not the index of one of `validator`'s fields
+    pub fn new(validator: &TicketValidator, field_indices: &[usize]) -> Self {
+        let names = field_indices
+            .iter()
+            .map(|&idx| validator.fields[idx].name.clone())
+            .collect();
+        Self { names }
+    }
+
+    /// The field names in the order of the columns
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn column_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// # Returns
+    /// * the value of each field of `ticket`, by name
+    /// * an error if `ticket` doesn't have exactly one value for each column
+    pub fn decode(&self, ticket: &Ticket) -> Result<HashMap<&str, u128>, TicketLengthError> {
+        self.decode_with_prefix(ticket, "")
+    }
+
+    /// Like `decode`, but only with the fields whose name starts with `prefix`
+    pub fn decode_with_prefix(
+        &self,
+        ticket: &Ticket,
+        prefix: &str,
+    ) -> Result<HashMap<&str, u128>, TicketLengthError> {
+        if ticket.values.len() != self.names.len() {
+            return Err(TicketLengthError {
+                ticket: 0,
+                expected: self.names.len(),
+                found: ticket.values.len(),
+            });
+        }
+
+        Ok(self
+            .names
+            .iter()
+            .zip(&ticket.values)
+            .filter(|(name, _)| name.starts_with(prefix))
+            .map(|(name, &value)| (name.as_str(), value))
+            .collect())
+    }
+
+    /// Decodes `ticket` into any type that can be deserialized from a map of field names to values
+    ///
+    /// Field names with spaces can be matched with `#[serde(rename = "departure location")]`,
+    /// and fields of the ticket that are not in the type are ignored unless it denies unknown fields.
+    pub fn decode_into<T: DeserializeOwned>(&self, ticket: &Ticket) -> Result<T, DecodeError> {
+        let values = self.decode(ticket).map_err(DecodeError::Length)?;
+        T::deserialize(MapDeserializer::new(values.into_iter())).map_err(DecodeError::Deserialize)
+    }
+
+    /// Writes the decoded tickets as CSV, with a header row of the field names in the order of the columns
+    pub fn write_csv<W: Write>(&self, writer: W, tickets: &[Ticket]) -> csv::Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(&self.names)?;
+        for ticket in tickets {
+            writer.write_record(ticket.values.iter().map(|v| v.to_string()))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Length(TicketLengthError),
+    Deserialize(serde::de::value::Error),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Length(e) => write!(f, "{}", e),
+            DecodeError::Deserialize(e) => write!(f, "failed to decode ticket: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tickets::{assignment::Assignment, TicketNotes};
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Seat {
+        row: u128,
+        #[serde(rename = "seat")]
+        number: u128,
+    }
+
+    #[test]
+    fn decode_ticket_test() {
+        let notes: TicketNotes = "class: 0-1 or 4-19\nrow: 0-5 or 8-19\nseat: 0-13 or 16-19\n\nyour ticket:\n11,12,13\n\nnearby tickets:\n3,9,18\n15,1,5\n5,14,9\n"
+            .parse()
+            .unwrap();
+        let validator = notes.validator();
+        let mapping = match validator.assign_fields(notes.nearby(), 2).unwrap() {
+            Assignment::Unique(field_indices) => FieldMapping::new(&validator, &field_indices),
+            other => panic!("expected a unique assignment, got {:?}", other),
+        };
+
+        let decoded = mapping.decode(notes.mine()).unwrap();
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(
+            (decoded["row"], decoded["class"], decoded["seat"]),
+            (11, 12, 13)
+        );
+
+        let decoded = mapping.decode_with_prefix(notes.mine(), "s").unwrap();
+        assert_eq!(decoded.into_iter().collect::<Vec<_>>(), vec![("seat", 13)]);
+
+        assert_eq!(
+            mapping.decode_into::<Seat>(notes.mine()).unwrap(),
+            Seat {
+                row: 11,
+                number: 13
+            }
+        );
+
+        let short: Ticket = "11,12".parse().unwrap();
+        assert_eq!(
+            mapping.decode(&short).unwrap_err().to_string(),
+            "ticket 0 has 2 values, expected 3"
+        );
+        assert!(matches!(
+            mapping.decode_into::<Seat>(&short),
+            Err(DecodeError::Length(_))
+        ));
+
+        let mut csv = Vec::new();
+        mapping.write_csv(&mut csv, notes.nearby()).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "row,class,seat\n3,9,18\n15,1,5\n5,14,9\n"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt::Display;
+
+use super::{column_count, Ticket, TicketLengthError, TicketValidator};
+
+/// Why tickets were discarded, and why each column can't be one of the other fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostics {
+    field_names: Vec<String>,
+    invalid_values: Vec<InvalidValue>,
+    columns: Vec<ColumnDiagnostics>,
+}
+
+impl Diagnostics {
+    /// Checks every ticket against the rules of `validator`
+    ///
+    /// Tickets with an invalid value are not used to eliminate candidates,
+    /// like they are discarded when assigning the fields.
+    /// # Returns
+    /// * an error if the tickets don't all have the same number of values
+    pub fn new(validator: &TicketValidator, tickets: &[Ticket]) -> Result<Self, TicketLengthError> {
+        let column_count = column_count(tickets)?;
+
+        let invalid_values: Vec<InvalidValue> = tickets
+            .iter()
+            .enumerate()
+            .flat_map(|(ticket, t)| {
+                t.values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &value)| validator.index.query(value).is_empty())
+                    .map(move |(column, &value)| InvalidValue {
+                        ticket,
+                        column,
+                        value,
+                    })
+            })
+            .collect();
+
+        let mut columns: Vec<ColumnDiagnostics> = (0..column_count)
+            .map(|_| ColumnDiagnostics {
+                candidates: (0..validator.fields.len()).collect(),
+                eliminated: Vec::new(),
+            })
+            .collect();
+
+        for (ticket, t) in tickets.iter().enumerate() {
+            if invalid_values.iter().any(|i| i.ticket == ticket) {
+                continue;
+            }
+
+            for (column, &value) in t.values.iter().enumerate() {
+                let valid_fields = validator.index.query(value);
+                let diagnostics = &mut columns[column];
+
+                let (kept, eliminated): (Vec<usize>, Vec<usize>) = diagnostics
+                    .candidates
+                    .iter()
+                    .partition(|field| valid_fields.contains(field));
+                diagnostics.candidates = kept;
+                diagnostics
+                    .eliminated
+                    .extend(eliminated.into_iter().map(|field| Elimination {
+                        field,
+                        ticket,
+                        value,
+                    }));
+            }
+        }
+
+        Ok(Self {
+            field_names: validator.fields.iter().map(|f| f.name.clone()).collect(),
+            invalid_values,
+            columns,
+        })
+    }
+
+    /// The values that match no rule, in the order of the tickets
+    pub fn invalid_values(&self) -> &[InvalidValue] {
+        &self.invalid_values
+    }
+
+    /// The indices of the tickets with at least one invalid value
+    pub fn invalid_tickets(&self) -> Vec<usize> {
+        let mut tickets: Vec<_> = self.invalid_values.iter().map(|i| i.ticket).collect();
+        tickets.dedup();
+        tickets
+    }
+
+    pub fn columns(&self) -> &[ColumnDiagnostics] {
+        &self.columns
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for invalid in &self.invalid_values {
+            writeln!(
+                f,
+                "ticket {}: value {} in column {} matches no rule",
+                invalid.ticket, invalid.value, invalid.column
+            )?;
+        }
+
+        for (column, diagnostics) in self.columns.iter().enumerate() {
+            let candidates: Vec<_> = diagnostics
+                .candidates
+                .iter()
+                .map(|&field| self.field_names[field].as_str())
+                .collect();
+            writeln!(f, "column {}: candidates {:?}", column, candidates)?;
+
+            for elimination in &diagnostics.eliminated {
+                writeln!(
+                    f,
+                    "  not '{}': ticket {} has value {}",
+                    self.field_names[elimination.field], elimination.ticket, elimination.value
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A value on a ticket that matches no rule
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidValue {
+    pub ticket: usize,
+    pub column: usize,
+    pub value: u128,
+}
+
+/// The fields a column could still be, and the fields it was ruled out from
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDiagnostics {
+    candidates: Vec<usize>,
+    eliminated: Vec<Elimination>,
+}
+
+impl ColumnDiagnostics {
+    /// The indices of the fields every valid ticket's value fits
+    pub fn candidates(&self) -> &[usize] {
+        &self.candidates
+    }
+
+    /// The fields the column can't be, in the order they were ruled out
+    pub fn eliminated(&self) -> &[Elimination] {
+        &self.eliminated
+    }
+}
+
+/// A field ruled out for a column by the first ticket whose value doesn't fit it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elimination {
+    pub field: usize,
+    pub ticket: usize,
+    pub value: u128,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tickets::TicketNotes;
+
+    #[test]
+    fn diagnostics_test() {
+        let notes: TicketNotes = "class: 0-1 or 4-19\nrow: 0-5 or 8-19\nseat: 0-13 or 16-19\n\nyour ticket:\n11,12,13\n\nnearby tickets:\n3,9,18\n15,1,5\n5,14,9\n15,20,5\n"
+            .parse()
+            .unwrap();
+        let diagnostics = notes.validator().diagnose(notes.nearby()).unwrap();
+
+        assert_eq!(
+            diagnostics.invalid_values(),
+            &[InvalidValue {
+                ticket: 3,
+                column: 1,
+                value: 20
+            }]
+        );
+        assert_eq!(diagnostics.invalid_tickets(), vec![3]);
+
+        assert_eq!(diagnostics.columns()[0].candidates(), &[1]);
+        assert_eq!(
+            diagnostics.columns()[0].eliminated(),
+            &[
+                Elimination {
+                    field: 0,
+                    ticket: 0,
+                    value: 3
+                },
+                Elimination {
+                    field: 2,
+                    ticket: 1,
+                    value: 15
+                }
+            ]
+        );
+
+        let report = diagnostics.to_string();
+        assert!(report.starts_with("ticket 3: value 20 in column 1 matches no rule\n"));
+        assert!(report
+            .contains("column 0: candidates [\"row\"]\n  not 'class': ticket 0 has value 3\n"));
+
+        let uneven: Vec<Ticket> = vec!["3,9".parse().unwrap(), "3,9,18".parse().unwrap()];
+        assert_eq!(
+            notes.validator().diagnose(&uneven).unwrap_err().to_string(),
+            "ticket 1 has 3 values, expected 2"
+        );
+    }
+}
@@ -0,0 +1,628 @@
+pub mod assignment;
+pub mod decode;
+pub mod diagnostics;
+pub mod ranges;
+
+use std::{fmt::Display, str::FromStr};
+
+use assignment::Assignment;
+use decode::FieldMapping;
+use diagnostics::Diagnostics;
+use ranges::{IntervalIndex, ParseRangeError, RangeSet};
+
+const YOUR_TICKET_HEADER: &str = "your ticket:";
+const NEARBY_TICKETS_HEADER: &str = "nearby tickets:";
+
+pub struct TicketValidator {
+    fields: Vec<Field>,
+    index: IntervalIndex,
+}
+
+impl TicketValidator {
+    fn new(fields: Vec<Field>) -> Self {
+        let index = IntervalIndex::new(fields.iter().map(|f| &f.ranges));
+        Self { fields, index }
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn get_valid_fields(&self, value: u128) -> Vec<&Field> {
+        self.index
+            .query(value)
+            .iter()
+            .map(|&idx| &self.fields[idx])
+            .collect()
+    }
+
+    /// Assigns a field to each column of the tickets, which must all be valid
+    ///
+    /// The indices in the returned assignment are indices into `fields`.
+    /// At most `limit` assignments are listed if there is more than one.
+    /// # Returns
+    /// * an error if the tickets don't all have the same number of values
+    pub fn assign_fields(
+        &self,
+        tickets: &[Ticket],
+        limit: usize,
+    ) -> Result<Assignment, TicketLengthError> {
+        let candidates = find_possible_fields_for_given_values(self, tickets)?;
+        Ok(assignment::assign(&candidates, self.fields.len(), limit))
+    }
+
+    /// Reports the invalid values of `tickets`, and which ticket rules out each field for each column
+    /// # Returns
+    /// * an error if the tickets don't all have the same number of values
+    pub fn diagnose(&self, tickets: &[Ticket]) -> Result<Diagnostics, TicketLengthError> {
+        Diagnostics::new(self, tickets)
+    }
+
+    fn validate_ticket_fields(&self, ticket: &Ticket) -> Vec<u128> {
+        let mut invalid_values = Vec::new();
+        for &value in &ticket.values {
+            if self.index.query(value).is_empty() {
+                invalid_values.push(value);
+            }
+        }
+        invalid_values
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ticket {
+    values: Vec<u128>,
+}
+
+impl Ticket {
+    fn new(values: Vec<u128>) -> Self {
+        Self { values }
+    }
+
+    pub fn values(&self) -> &[u128] {
+        &self.values
+    }
+}
+
+impl FromStr for Ticket {
+    type Err = ParseTicketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 7,3,47
+        // only a line ending is allowed after the last value, an empty value anywhere is an error
+        let values = s
+            .trim_end()
+            .split(',')
+            .map(|p| p.trim())
+            .enumerate()
+            .map(|(column, p)| {
+                p.parse().map_err(|_| ParseTicketError {
+                    column,
+                    value: p.to_owned(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(values))
+    }
+}
+
+/// A value on a ticket that is not a number, or is missing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseTicketError {
+    column: usize,
+    value: String,
+}
+
+impl Display for ParseTicketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "value '{}' in column {} is not a number",
+            self.value, self.column
+        )
+    }
+}
+
+/// A ticket with a different number of values than the first ticket, or than the columns of a mapping
+#[derive(Debug, Clone, PartialEq)]
+pub struct TicketLengthError {
+    ticket: usize,
+    expected: usize,
+    found: usize,
+}
+
+impl TicketLengthError {
+    /// The index of the ticket with the wrong number of values
+    pub fn ticket(&self) -> usize {
+        self.ticket
+    }
+
+    pub fn expected(&self) -> usize {
+        self.expected
+    }
+
+    pub fn found(&self) -> usize {
+        self.found
+    }
+}
+
+impl Display for TicketLengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ticket {} has {} values, expected {}",
+            self.ticket, self.found, self.expected
+        )
+    }
+}
+
+/// # Returns
+/// * the number of values of each ticket, or an error for the first ticket with a different number
+fn column_count(tickets: &[Ticket]) -> Result<usize, TicketLengthError> {
+    let expected = tickets.first().map_or(0, |t| t.values.len());
+
+    match tickets.iter().position(|t| t.values.len() != expected) {
+        Some(ticket) => Err(TicketLengthError {
+            ticket,
+            expected,
+            found: tickets[ticket].values.len(),
+        }),
+        None => Ok(expected),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Field {
+    name: String,
+    ranges: RangeSet,
+}
+
+impl Field {
+    fn new(name: String, ranges: RangeSet) -> Self {
+        Self { name, ranges }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ranges(&self) -> &RangeSet {
+        &self.ranges
+    }
+
+    pub fn validate_value(&self, value: u128) -> bool {
+        self.ranges.contains(value)
+    }
+}
+
+impl FromStr for Field {
+    type Err = ParseFieldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // class: 1-3 or 5-7 or 10-
+
+        let parts: Vec<_> = s.split(": ").collect();
+
+        if parts.len() != 2 || parts[0].trim().is_empty() {
+            return Err(ParseFieldError::MissingName);
+        }
+
+        let name = parts[0].trim().to_owned();
+        let ranges = parts[1].parse().map_err(ParseFieldError::InvalidRange)?;
+
+        Ok(Field::new(name, ranges))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseFieldError {
+    /// The rule does not start with a name followed by ": "
+    MissingName,
+    InvalidRange(ParseRangeError),
+}
+
+impl Display for ParseFieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseFieldError::MissingName => write!(f, "missing field name"),
+            ParseFieldError::InvalidRange(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// The notes taken about tickets: the rules for each field, your own ticket and the nearby tickets
+#[derive(Debug, Clone)]
+pub struct TicketNotes {
+    rules: Vec<Field>,
+    mine: Ticket,
+    nearby: Vec<Ticket>,
+}
+
+impl TicketNotes {
+    pub fn rules(&self) -> &[Field] {
+        &self.rules
+    }
+
+    pub fn mine(&self) -> &Ticket {
+        &self.mine
+    }
+
+    pub fn nearby(&self) -> &[Ticket] {
+        &self.nearby
+    }
+
+    pub fn validator(&self) -> TicketValidator {
+        TicketValidator::new(self.rules.clone())
+    }
+}
+
+impl FromStr for TicketNotes {
+    type Err = ParseTicketNotesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // sections are separated by blank lines, and `lines` takes care of both LF and CRLF
+        let mut sections: Vec<Vec<(usize, &str)>> = vec![Vec::new()];
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            let current = sections.last_mut().unwrap(); // safe to unwrap because sections is never empty
+            if line.is_empty() {
+                if !current.is_empty() {
+                    sections.push(Vec::new());
+                }
+            } else {
+                current.push((idx + 1, line));
+            }
+        }
+        sections.retain(|section| !section.is_empty());
+
+        if sections.len() > 3 {
+            return Err(ParseTicketNotesError::UnexpectedSection {
+                line: sections[3][0].0,
+            });
+        }
+
+        let mut sections = sections.into_iter();
+        let rule_lines = sections
+            .next()
+            .ok_or(ParseTicketNotesError::MissingSection("rules"))?;
+        let mine_lines = sections
+            .next()
+            .ok_or(ParseTicketNotesError::MissingSection(YOUR_TICKET_HEADER))?;
+        let nearby_lines = sections
+            .next()
+            .ok_or(ParseTicketNotesError::MissingSection(NEARBY_TICKETS_HEADER))?;
+
+        let rules = rule_lines
+            .iter()
+            .map(|&(line, content)| {
+                content
+                    .parse()
+                    .map_err(|error| ParseTicketNotesError::InvalidRule { line, error })
+            })
+            .collect::<Result<Vec<Field>, _>>()?;
+
+        let mine_tickets = parse_ticket_section(&mine_lines, YOUR_TICKET_HEADER)?;
+        if mine_tickets.len() != 1 {
+            return Err(ParseTicketNotesError::ExpectedOneTicket {
+                line: mine_lines[0].0,
+                found: mine_tickets.len(),
+            });
+        }
+        let mine = mine_tickets.into_iter().next().unwrap(); // safe to unwrap because the length was checked
+
+        let nearby = parse_ticket_section(&nearby_lines, NEARBY_TICKETS_HEADER)?;
+
+        for (&(line, _), ticket) in nearby_lines[1..].iter().zip(&nearby) {
+            if ticket.values.len() != mine.values.len() {
+                return Err(ParseTicketNotesError::WrongTicketLength {
+                    line,
+                    expected: mine.values.len(),
+                    found: ticket.values.len(),
+                });
+            }
+        }
+
+        Ok(TicketNotes {
+            rules,
+            mine,
+            nearby,
+        })
+    }
+}
+
+/// Parses a section starting with `header`, followed by one ticket per line
+fn parse_ticket_section(
+    lines: &[(usize, &str)],
+    header: &'static str,
+) -> Result<Vec<Ticket>, ParseTicketNotesError> {
+    let (header_line, first) = lines[0];
+    if first != header {
+        return Err(ParseTicketNotesError::MissingHeader {
+            line: header_line,
+            expected: header,
+        });
+    }
+
+    lines[1..]
+        .iter()
+        .map(|&(line, content)| {
+            content
+                .parse()
+                .map_err(|error| ParseTicketNotesError::InvalidTicket { line, error })
+        })
+        .collect()
+}
+
+/// An error from parsing ticket notes, with 1-indexed line numbers
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseTicketNotesError {
+    /// The notes ended before the given section
+    MissingSection(&'static str),
+    /// A section does not start with its header
+    MissingHeader {
+        line: usize,
+        expected: &'static str,
+    },
+    InvalidRule {
+        line: usize,
+        error: ParseFieldError,
+    },
+    InvalidTicket {
+        line: usize,
+        error: ParseTicketError,
+    },
+    /// The "your ticket:" section does not contain exactly one ticket
+    ExpectedOneTicket {
+        line: usize,
+        found: usize,
+    },
+    /// A nearby ticket has a different number of values than your ticket
+    WrongTicketLength {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// There is another section after the nearby tickets
+    UnexpectedSection {
+        line: usize,
+    },
+}
+
+impl Display for ParseTicketNotesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseTicketNotesError::MissingSection(section) => {
+                write!(f, "missing section '{}'", section)
+            }
+            ParseTicketNotesError::MissingHeader { line, expected } => {
+                write!(f, "line {}: expected '{}'", line, expected)
+            }
+            ParseTicketNotesError::InvalidRule { line, error } => {
+                write!(f, "line {}: invalid rule: {}", line, error)
+            }
+            ParseTicketNotesError::InvalidTicket { line, error } => {
+                write!(f, "line {}: invalid ticket: {}", line, error)
+            }
+            ParseTicketNotesError::ExpectedOneTicket { line, found } => {
+                write!(f, "line {}: expected 1 ticket, found {}", line, found)
+            }
+            ParseTicketNotesError::WrongTicketLength {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} values, found {}",
+                line, expected, found
+            ),
+            ParseTicketNotesError::UnexpectedSection { line } => {
+                write!(f, "line {}: unexpected section", line)
+            }
+        }
+    }
+}
+
+pub fn read_ticket_notes_from_file(file: &str) -> Result<TicketNotes, ParseTicketNotesError> {
+    std::fs::read_to_string(file).unwrap().parse()
+}
+
+pub fn solve_day16_part1_from_file(file: &str) -> u128 {
+    let notes = read_ticket_notes_from_file(file).unwrap();
+    let validator = notes.validator();
+
+    let mut sum_of_invalid = 0;
+
+    for ticket in notes.nearby() {
+        let invalid_values = validator.validate_ticket_fields(ticket);
+        for invalid_value in invalid_values {
+            sum_of_invalid += invalid_value;
+        }
+    }
+
+    sum_of_invalid
+}
+
+fn remove_invalid_tickets(validator: &TicketValidator, tickets: &mut Vec<Ticket>) {
+    for idx in (0..tickets.len()).rev() {
+        let invalid_values = validator.validate_ticket_fields(&tickets[idx]);
+        if !invalid_values.is_empty() {
+            tickets.remove(idx);
+        }
+    }
+}
+
+pub fn solve_day16_part2_from_file(file: &str) -> u128 {
+    let notes = read_ticket_notes_from_file(file).unwrap();
+    let validator = notes.validator();
+
+    let mut tickets = notes.nearby().to_vec();
+    tickets.insert(0, notes.mine().clone());
+
+    remove_invalid_tickets(&validator, &mut tickets);
+
+    // safe to unwrap because the nearby tickets were checked to be as long as yours when parsing
+    let mapping = match validator.assign_fields(&tickets, 2).unwrap() {
+        Assignment::Unique(field_indices) => FieldMapping::new(&validator, &field_indices),
+        Assignment::Ambiguous(_) => panic!("the fields can be assigned in more than one way"),
+        Assignment::Impossible(conflict) => panic!("the fields can't be assigned: {}", conflict),
+    };
+
+    // safe to unwrap because your ticket was one of the tickets the fields were assigned from
+    mapping
+        .decode_with_prefix(notes.mine(), "departure")
+        .unwrap()
+        .values()
+        .product()
+}
+
+/// # Returns
+/// * the indices of the fields that every ticket's value fits, for each column
+/// * an error if the tickets don't all have the same number of values
+fn find_possible_fields_for_given_values(
+    validator: &TicketValidator,
+    tickets: &[Ticket],
+) -> Result<Vec<Vec<usize>>, TicketLengthError> {
+    let column_count = column_count(tickets)?;
+
+    Ok((0..column_count)
+        .map(|column| {
+            let sets = tickets
+                .iter()
+                .map(|t| {
+                    validator
+                        .index
+                        .query(t.values[column])
+                        .iter()
+                        .copied()
+                        .collect()
+                })
+                .collect();
+
+            let mut valid_fields: Vec<usize> = crate::intersection_many::<usize>(sets)
+                .into_iter()
+                .collect();
+            valid_fields.sort_unstable();
+            valid_fields
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_field_test() {
+        assert_eq!(
+            Field::from_str("class: 1-3 or 5-7").unwrap(),
+            Field::new(String::from("class"), RangeSet::new(vec![1..=3, 5..=7]))
+        );
+
+        let field = Field::from_str("seat: 8-9 or 1-3 or 4-5 or 50-").unwrap();
+        assert_eq!(field.ranges().ranges(), &[1..=5, 8..=9, 50..=u128::MAX]);
+        assert!(field.validate_value(4) && !field.validate_value(6));
+
+        assert!(matches!(
+            Field::from_str("class: 1-3 or 7-5"),
+            Err(ParseFieldError::InvalidRange(_))
+        ));
+    }
+
+    #[test]
+    fn parse_ticket_test() {
+        assert_eq!(
+            Ticket::from_str("7,3,47\r\n").unwrap().values(),
+            &[7, 3, 47]
+        );
+
+        for (input, column) in &[("7,,47", 1), ("7,3,", 2), (",3,47", 0), ("", 0)] {
+            assert_eq!(
+                Ticket::from_str(input).unwrap_err(),
+                ParseTicketError {
+                    column: *column,
+                    value: String::new()
+                }
+            );
+        }
+
+        assert_eq!(
+            Ticket::from_str("7,,x").unwrap_err(),
+            ParseTicketError {
+                column: 1,
+                value: String::new()
+            }
+        );
+    }
+
+    #[test]
+    fn assign_fields_test() {
+        let notes: TicketNotes = "class: 0-1 or 4-19\nrow: 0-5 or 8-19\nseat: 0-13 or 16-19\n\nyour ticket:\n11,12,13\n\nnearby tickets:\n3,9,18\n15,1,5\n5,14,9\n"
+            .parse()
+            .unwrap();
+        let validator = notes.validator();
+
+        assert_eq!(
+            validator.assign_fields(notes.nearby(), 10),
+            Ok(Assignment::Unique(vec![1, 0, 2]))
+        );
+
+        let uneven: Vec<Ticket> = vec!["3,9,18".parse().unwrap(), "3,9".parse().unwrap()];
+        assert_eq!(
+            validator.assign_fields(&uneven, 10),
+            Err(TicketLengthError {
+                ticket: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn parse_ticket_notes_test() {
+        let lf = "class: 1-3 or 5-7\nrow: 6-11 or 33-44\n\nyour ticket:\n7,1,14\n\nnearby tickets:\n7,3,47\n40,4,50\n";
+        let crlf = lf.replace('\n', "\r\n");
+
+        for input in &[lf.to_string(), crlf] {
+            let notes: TicketNotes = input.parse().unwrap();
+            assert_eq!(notes.rules().len(), 2);
+            assert_eq!(notes.mine().values(), &[7, 1, 14]);
+            assert_eq!(notes.nearby().len(), 2);
+        }
+
+        assert_eq!(
+            "class: 1-3 or 5-7\n\nyour ticket:\n7,1,14\n\nnearby tickets:\n7,x,47\n"
+                .parse::<TicketNotes>()
+                .unwrap_err(),
+            ParseTicketNotesError::InvalidTicket {
+                line: 7,
+                error: ParseTicketError {
+                    column: 1,
+                    value: "x".to_string()
+                }
+            }
+        );
+
+        assert_eq!(
+            "class: 1-3 or 5-7\n\nyour ticket:\n7,1,14\n\nnearby tickets:\n7,,x\n"
+                .parse::<TicketNotes>()
+                .unwrap_err(),
+            ParseTicketNotesError::InvalidTicket {
+                line: 7,
+                error: ParseTicketError {
+                    column: 1,
+                    value: String::new()
+                }
+            }
+        );
+
+        assert_eq!(
+            "class: 1-3 or 5-7\n\n7,1,14\n\nnearby tickets:\n7,3,47\n"
+                .parse::<TicketNotes>()
+                .unwrap_err(),
+            ParseTicketNotesError::MissingHeader {
+                line: 3,
+                expected: YOUR_TICKET_HEADER
+            }
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
+
+/// A set of values made of inclusive ranges
+///
+/// The ranges are kept sorted, and overlapping or adjacent ranges are merged,
+/// so two sets containing the same values are equal.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RangeSet {
+    ranges: Vec<RangeInclusive<u128>>,
+}
+
+impl RangeSet {
+    pub fn new<I: IntoIterator<Item = RangeInclusive<u128>>>(ranges: I) -> Self {
+        let mut ranges: Vec<_> = ranges.into_iter().filter(|r| !r.is_empty()).collect();
+        ranges.sort_by_key(|r| *r.start());
+
+        let mut merged: Vec<RangeInclusive<u128>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start().saturating_sub(1) <= *last.end() => {
+                    if range.end() > last.end() {
+                        *last = *last.start()..=*range.end();
+                    }
+                }
+                _ => merged.push(range),
+            }
+        }
+
+        Self { ranges: merged }
+    }
+
+    pub fn ranges(&self) -> &[RangeInclusive<u128>] {
+        &self.ranges
+    }
+
+    pub fn contains(&self, value: u128) -> bool {
+        // the ranges are sorted and disjoint, so only the last one starting at or before `value` can contain it
+        let idx = self.ranges.partition_point(|r| *r.start() <= value);
+        idx > 0 && value <= *self.ranges[idx - 1].end()
+    }
+}
+
+impl FromStr for RangeSet {
+    type Err = ParseRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // format is: 1-3 or 5-7 or 10-
+        let ranges = s
+            .split(" or ")
+            .map(|r| parse_range(r.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(ranges))
+    }
+}
+
+/// Parses "low-high", "low-" (no upper bound) or "-high" (no lower bound)
+fn parse_range(range: &str) -> Result<RangeInclusive<u128>, ParseRangeError> {
+    let invalid = || ParseRangeError(range.to_owned());
+
+    let mut bounds = range.splitn(2, '-').map(|p| p.trim());
+    let (low, high) = match (bounds.next(), bounds.next()) {
+        (Some(low), Some(high)) if !low.is_empty() || !high.is_empty() => (low, high),
+        _ => return Err(invalid()),
+    };
+
+    let parse_bound = |bound: &str, open: u128| {
+        if bound.is_empty() {
+            Ok(open)
+        } else {
+            bound.parse().map_err(|_| invalid())
+        }
+    };
+    let low = parse_bound(low, u128::MIN)?;
+    let high = parse_bound(high, u128::MAX)?;
+
+    if low > high {
+        return Err(invalid());
+    }
+    Ok(low..=high)
+}
+
+impl Display for RangeSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, range) in self.ranges.iter().enumerate() {
+            if idx > 0 {
+                write!(f, " or ")?;
+            }
+            if *range.start() != u128::MIN {
+                write!(f, "{}", range.start())?;
+            }
+            write!(f, "-")?;
+            if *range.end() != u128::MAX {
+                write!(f, "{}", range.end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A range that is not of the form "low-high", "low-" or "-high"
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseRangeError(String);
+
+impl Display for ParseRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid range '{}'", self.0)
+    }
+}
+
+/// Finds which of many range sets contain a value, without checking each of them
+///
+/// The values are split into segments at every range boundary, and each segment
+/// knows which sets cover it, so a lookup is a binary search over the segments.
+#[derive(Debug, Clone, Default)]
+pub struct IntervalIndex {
+    /// The first value of each segment, sorted
+    starts: Vec<u128>,
+    /// The indices of the sets covering each segment
+    covering: Vec<Vec<usize>>,
+}
+
+impl IntervalIndex {
+    pub fn new<'a, I: IntoIterator<Item = &'a RangeSet>>(sets: I) -> Self {
+        let sets: Vec<_> = sets.into_iter().collect();
+
+        let mut starts: Vec<u128> = sets
+            .iter()
+            .flat_map(|set| set.ranges())
+            .flat_map(|r| {
+                // a range ending at u128::MAX doesn't start a new segment after it
+                std::iter::once(*r.start()).chain(r.end().checked_add(1))
+            })
+            .collect();
+        starts.sort_unstable();
+        starts.dedup();
+
+        let mut covering = vec![Vec::new(); starts.len()];
+        for (set_idx, set) in sets.iter().enumerate() {
+            for range in set.ranges() {
+                let first = starts.partition_point(|&s| s < *range.start());
+                let last = starts.partition_point(|&s| s <= *range.end());
+                for segment in &mut covering[first..last] {
+                    segment.push(set_idx);
+                }
+            }
+        }
+
+        Self { starts, covering }
+    }
+
+    /// # Returns
+    /// * the indices of the sets containing `value`, in the order the sets were given
+    pub fn query(&self, value: u128) -> &[usize] {
+        match self.starts.partition_point(|&s| s <= value) {
+            0 => &[],
+            idx => &self.covering[idx - 1],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_set_test() {
+        let set: RangeSet = "10-12 or 1-3 or 4-5 or 11-20".parse().unwrap();
+        assert_eq!(set.ranges(), &[1..=5, 10..=20]);
+        assert_eq!(set.to_string(), "1-5 or 10-20");
+        assert!(set.contains(1) && set.contains(5) && set.contains(20));
+        assert!(!set.contains(0) && !set.contains(6) && !set.contains(21));
+
+        let open: RangeSet = "-3 or 100-".parse().unwrap();
+        assert!(open.contains(0) && open.contains(u128::MAX) && !open.contains(50));
+        assert_eq!(open.to_string(), "-3 or 100-");
+
+        assert_eq!(
+            "5-3".parse::<RangeSet>(),
+            Err(ParseRangeError("5-3".to_string()))
+        );
+        assert!("-".parse::<RangeSet>().is_err());
+    }
+
+    #[test]
+    fn interval_index_test() {
+        let sets: Vec<RangeSet> = vec!["1-3 or 5-7", "6-11 or 33-44", "13-40 or 45-"]
+            .into_iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let index = IntervalIndex::new(&sets);
+
+        for value in 0..60 {
+            let expected: Vec<usize> = (0..sets.len())
+                .filter(|&idx| sets[idx].contains(value))
+                .collect();
+            assert_eq!(index.query(value), expected.as_slice(), "value {}", value);
+        }
+        assert_eq!(index.query(u128::MAX), &[2]);
+    }
+}